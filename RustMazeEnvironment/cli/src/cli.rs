@@ -1,5 +1,23 @@
-use clap::{Parser, Subcommand};
-use maze_library::{exploring_algorithms::explore_handler::ExploreAlgorithm, maze_gen::maze_gen_handler::MazeType, solving_algorithms::solve_handler::SolveAlgorithm};
+use clap::{Parser, Subcommand, ValueEnum};
+use maze_library::{
+    exploring_algorithms::explore_handler::ExploreAlgorithm,
+    maze_gen::{
+        generator_registry::{find_generator, registered_generator_names},
+        maze_gen_handler::GenerationParameters,
+    },
+    solving_algorithms::solve_handler::SolveAlgorithm,
+    topology::Topology,
+};
+
+fn parse_generator_name(name: &str) -> Result<String, String> {
+    match find_generator(name) {
+        Some(generator) => Ok(generator.name()),
+        None => Err(format!(
+            "possible values: {}",
+            registered_generator_names().join(", ")
+        )),
+    }
+}
 
 
 #[derive(Parser)]
@@ -10,6 +28,13 @@ pub struct Cli {
     pub command: Commands,
 }
 
+#[derive(ValueEnum, Clone, Debug)]
+pub enum StatsFormat {
+    Table,
+    Json,
+    Csv,
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// Solve a maze with given parameters
@@ -19,8 +44,27 @@ pub enum Commands {
         explore_algoithm: ExploreAlgorithm,
 
         /// maze generation algotithm
-        #[arg(short, long, value_enum, default_value_t=MazeType::Kruzkals)]
-        gen_algotithm: MazeType,
+        #[arg(short, long, value_parser = parse_generator_name, default_value = "Kruzkals")]
+        gen_algotithm: String,
+
+        #[command(flatten)]
+        generation_parameters: GenerationParameters,
+
+        /// shape of the maze cells
+        #[arg(long, value_enum, default_value_t = Topology::Square)]
+        topology: Topology,
+
+        /// join each edge of the maze to the opposite one
+        #[arg(long)]
+        wrap: bool,
+
+        /// floors stacked up by stairs, each width by length
+        #[arg(long, default_value_t = 1)]
+        levels: usize,
+
+        /// only show this level of layered mazes, every level side by side if left out
+        #[arg(long)]
+        level: Option<usize>,
 
         /// Maze solving algorithm
         #[arg(short, long, value_enum, default_value_t=SolveAlgorithm::Dijkstra)]
@@ -50,6 +94,10 @@ pub enum Commands {
         /// Number of files to iterate over
         #[arg(short, long, default_value_t = 5)]
         count: usize,
+
+        /// only show this level of layered mazes, every level side by side if left out
+        #[arg(long)]
+        level: Option<usize>,
     },
     /// Display a maze with given parameters
     ColouredDisplay {
@@ -60,12 +108,35 @@ pub enum Commands {
         /// Number of files to iterate over
         #[arg(short, long, default_value_t = 5)]
         count: usize,
+
+        /// only show this level of layered mazes, every level side by side if left out
+        #[arg(long)]
+        level: Option<usize>,
     },
     ShowGenBias {
  
          /// maze generation algotithm
-         #[arg(short, long, value_enum, default_value_t=MazeType::BinaryTree)]
-         gen_algotithm: MazeType,
+         #[arg(short, long, value_parser = parse_generator_name, default_value = "BinaryTree")]
+         gen_algotithm: String,
+
+         #[command(flatten)]
+         generation_parameters: GenerationParameters,
+
+         /// shape of the maze cells
+         #[arg(long, value_enum, default_value_t = Topology::Square)]
+         topology: Topology,
+
+         /// join each edge of the maze to the opposite one
+         #[arg(long)]
+         wrap: bool,
+
+         /// floors stacked up by stairs, each width by length
+         #[arg(long, default_value_t = 1)]
+         levels: usize,
+
+         /// only show this level of layered mazes, every level side by side if left out
+         #[arg(long)]
+         level: Option<usize>,
          /// number of mazes to solve
          #[arg(short, long, default_value_t = 10)]
          count: usize,
@@ -80,5 +151,52 @@ pub enum Commands {
          #[arg(short, long, default_value_t = 0)]
          removed_walls: usize,
     },
+    /// Print generator bias statistics without opening a window
+    GenStats {
+        /// maze generation algotithms to compare, every registered generator if left empty
+        #[arg(short, long, value_parser = parse_generator_name, num_args = 1..)]
+        gen_algotithms: Vec<String>,
+
+        #[command(flatten)]
+        generation_parameters: GenerationParameters,
+
+        /// shape of the maze cells
+        #[arg(long, value_enum, default_value_t = Topology::Square)]
+        topology: Topology,
+
+        /// join each edge of the maze to the opposite one
+        #[arg(long)]
+        wrap: bool,
+
+        /// floors stacked up by stairs, each width by length
+        #[arg(long, default_value_t = 1)]
+        levels: usize,
+
+        /// number of mazes per generator
+        #[arg(short, long, default_value_t = 100)]
+        count: usize,
+        /// Width of maze
+        #[arg(short, long, default_value_t = 19)]
+        width: usize,
+        /// Heigh of maze
+        #[arg(short, long, default_value_t = 19)]
+        length: usize,
+
+        /// additional walls to remove
+        #[arg(short, long, default_value_t = 0)]
+        removed_walls: usize,
+
+        /// base seed, maze i of each generator uses seed + i
+        #[arg(short, long)]
+        seed: Option<u64>,
+
+        /// output format
+        #[arg(short, long, value_enum, default_value_t = StatsFormat::Table)]
+        format: StatsFormat,
+
+        /// file to write to instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
     
 }
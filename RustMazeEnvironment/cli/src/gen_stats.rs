@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use maze_library::{
+    direction::Direction,
+    environment::environment::{Coordinate, Environment},
+    maze_stats::MazeStats,
+};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricSummary {
+    pub metric: String,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// half width of the 95% confidence interval around the mean
+    pub ci95: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GeneratorSummary {
+    pub generator: String,
+    pub samples: usize,
+    pub metrics: Vec<MetricSummary>,
+}
+
+fn share(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    count as f64 / total as f64
+}
+
+/// Structural metrics plus the direction each cell first steps in on its shortest path to the goal
+pub fn maze_metrics(
+    env: &Environment,
+    distance_map: &HashMap<Coordinate, (usize, Coordinate)>,
+) -> Vec<(String, f64)> {
+    let stats = MazeStats::new(&env.maze);
+    let corridor_segments: usize = stats.corridor_length_histogram.values().sum();
+    let corridor_cells: usize = stats
+        .corridor_length_histogram
+        .iter()
+        .map(|(length, count)| length * count)
+        .sum();
+    let total_openings: usize = stats.open_walls_by_direction.values().sum();
+
+    let mut goal_steps: HashMap<Direction, usize> = HashMap::new();
+    for (coordinate, (_, previous)) in distance_map.iter() {
+        if let Some(direction) = env.maze.direction_to(*coordinate, *previous) {
+            *goal_steps.entry(direction).or_insert(0) += 1;
+        }
+    }
+    let total_goal_steps: usize = goal_steps.values().sum();
+    let distances: Vec<usize> = distance_map.values().map(|(distance, _)| *distance).collect();
+
+    let mut metrics = vec![
+        ("dead_ends".to_string(), stats.dead_ends as f64),
+        ("junctions".to_string(), stats.junctions as f64),
+        ("corridors".to_string(), stats.corridors as f64),
+        (
+            "mean_corridor_length".to_string(),
+            share(corridor_cells, corridor_segments),
+        ),
+        ("straightness_ratio".to_string(), stats.straightness_ratio as f64),
+        ("turn_ratio".to_string(), stats.turn_ratio as f64),
+        ("solution_length".to_string(), stats.solution_length as f64),
+        ("tortuosity".to_string(), stats.tortuosity as f64),
+        (
+            "mean_goal_distance".to_string(),
+            share(distances.iter().sum(), distances.len()),
+        ),
+        (
+            "max_goal_distance".to_string(),
+            *distances.iter().max().unwrap_or(&0) as f64,
+        ),
+    ];
+    for direction in env.maze.directions() {
+        metrics.push((
+            format!("open_{}", direction.to_string().to_lowercase()),
+            share(
+                *stats.open_walls_by_direction.get(direction).unwrap_or(&0),
+                total_openings,
+            ),
+        ));
+    }
+    for direction in env.maze.directions() {
+        metrics.push((
+            format!("goal_step_{}", direction.to_string().to_lowercase()),
+            share(*goal_steps.get(direction).unwrap_or(&0), total_goal_steps),
+        ));
+    }
+    metrics
+}
+
+pub fn summarise(generator: &str, samples: &[Vec<(String, f64)>]) -> GeneratorSummary {
+    let metric_names: Vec<String> = samples
+        .first()
+        .map(|metrics| metrics.iter().map(|(name, _)| name.clone()).collect())
+        .unwrap_or_default();
+    let n = samples.len() as f64;
+    let metrics = metric_names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let values: Vec<f64> = samples.iter().map(|metrics| metrics[index].1).collect();
+            let mean = values.iter().sum::<f64>() / n;
+            let std_dev = if values.len() > 1 {
+                (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+            } else {
+                0.0
+            };
+            MetricSummary {
+                metric: name.clone(),
+                mean,
+                std_dev,
+                ci95: 1.96 * std_dev / n.sqrt(),
+            }
+        })
+        .collect();
+    GeneratorSummary {
+        generator: generator.to_string(),
+        samples: samples.len(),
+        metrics,
+    }
+}
+
+/// One row per metric and one column per generator showing mean ± ci95
+pub fn format_table(summaries: &[GeneratorSummary]) -> String {
+    let mut rows: Vec<Vec<String>> = vec![];
+    let mut header = vec!["metric".to_string()];
+    header.extend(
+        summaries
+            .iter()
+            .map(|summary| format!("{} (n={})", summary.generator, summary.samples)),
+    );
+    rows.push(header);
+    if let Some(first) = summaries.first() {
+        for (index, metric) in first.metrics.iter().enumerate() {
+            let mut row = vec![metric.metric.clone()];
+            row.extend(summaries.iter().map(|summary| {
+                let value = &summary.metrics[index];
+                format!("{:.3} ± {:.3}", value.mean, value.ci95)
+            }));
+            rows.push(row);
+        }
+    }
+
+    let column_widths: Vec<usize> = (0..rows[0].len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(column_widths.iter())
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn format_csv(summaries: &[GeneratorSummary]) -> String {
+    let mut lines = vec!["generator,samples,metric,mean,std_dev,ci95".to_string()];
+    for summary in summaries {
+        for metric in summary.metrics.iter() {
+            lines.push(format!(
+                "{},{},{},{},{},{}",
+                summary.generator,
+                summary.samples,
+                metric.metric,
+                metric.mean,
+                metric.std_dev,
+                metric.ci95
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+pub fn format_json(summaries: &[GeneratorSummary]) -> String {
+    match serde_json::to_string_pretty(summaries) {
+        Ok(json) => json,
+        Err(e) => e.to_string(),
+    }
+}
@@ -1,12 +1,17 @@
 use std::{
+    collections::HashMap,
     fs::File,
     io::{Error, Read},
 };
 
 use maze_library::{
-    environment::environment::Environment,
+    environment::environment::{Coordinate, Environment},
     environment_config::EnvConfig,
-    maze_gen::maze_gen_handler::{select_maze_algorithm, MazeType},
+    maze_gen::{
+        generator_registry::{find_generator, run_generator},
+        maze_gen_handler::GenerationParameters,
+    },
+    solving_algorithms::dijkstra::dijkstra_graph,
 };
 use regex::Regex;
 
@@ -30,42 +35,68 @@ pub fn read_environment_from_file(filename: &str) -> Result<Environment, Error>
 }
 
 pub fn generate_environment_list(
-    algorithm: &MazeType,
-    width: usize,
-    height: usize,
+    algorithm: &str,
+    parameters: &GenerationParameters,
+    config: &EnvConfig,
     count: usize,
     removed_walls: usize,
     rng_seed: Option<u64>,
 ) -> Vec<Environment> {
     let mut environments = vec![];
-    for _ in 0..count {
+    for index in 0..count {
+        //offset the seed so a seeded list is reproducible without every maze being identical
         environments.push(generate_environment(
             algorithm,
-            width,
-            height,
+            parameters,
+            config,
             removed_walls,
-            rng_seed,
+            rng_seed.map(|seed| seed.wrapping_add(index as u64)),
         ));
     }
     environments
 }
 
 pub fn generate_environment(
-    algorithm: &MazeType,
-    width: usize,
-    height: usize,
+    algorithm: &str,
+    parameters: &GenerationParameters,
+    config: &EnvConfig,
     removed_walls: usize,
     rng_seed: Option<u64>,
 ) -> Environment {
-    let mut env = Environment::new(EnvConfig::new_rust_config(width, height));
-    let walls = select_maze_algorithm(&env.maze, rng_seed, algorithm);
+    let mut env = Environment::new_with_seed(config.clone(), rng_seed);
+    if let Some(shape) = &parameters.mask {
+        env.apply_mask(shape)
+            .unwrap_or_else(|e| panic!("could not apply the mask: {}", e));
+    }
+    if parameters.weave_density > 0.0 {
+        env.add_crossings(parameters.weave_density);
+    }
+    let generator =
+        find_generator(algorithm).expect("generator name is checked when the cli is parsed");
+    let walls = run_generator(&*generator, &env.maze, env.rng.next_rng(), parameters);
 
     env.maze.break_walls_for_path(walls);
-    let extra_walls = env.maze.break_random_walls(removed_walls);
-    env.maze.break_walls_for_path(extra_walls);
+    env.break_random_walls(removed_walls);
+    if parameters.door_count > 0 {
+        env.add_doors_and_keys(parameters.door_count);
+    }
+    if parameters.portal_count > 0 {
+        env.add_portals(parameters.portal_count);
+    }
+    if parameters.terrain_max_cost > 1 {
+        env.add_terrain(parameters.terrain_max_cost, parameters.terrain_scale);
+    }
     env
 }
 
+/// Shortest distance and next step from every cell to the nearest goal cell, walking single
+/// cells
+pub fn dijkstra_map_from_end(env: &mut Environment) -> HashMap<Coordinate, (usize, Coordinate)> {
+    env.weighted_graph = env.maze.convert_to_weighted_graph(None, false);
+    let goal_cells: Vec<Coordinate> = env.maze.goal_regions().into_iter().flatten().collect();
+    dijkstra_graph(env, &goal_cells)
+}
+
 pub fn extract_prefix(path: &str) -> (String, usize) {
     // Define the regular expression to capture everything up until the last number and .json
     let re: Regex = Regex::new(r"^(.*\/[a-zA-Z0-9_]*?)(\d+)\.json$").unwrap();
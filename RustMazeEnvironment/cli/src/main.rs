@@ -1,15 +1,18 @@
 
 use clap::Parser;
 use cli::{Cli, Commands};
+use cli::StatsFormat;
+use gen_stats::{format_csv, format_json, format_table, maze_metrics, summarise};
 use handler_functions::{
-    extract_prefix, generate_environment_list, read_environment_from_file,
+    dijkstra_map_from_end, extract_prefix, generate_environment_list, read_environment_from_file,
 };
 use log::info;
 use macroquad::window::Conf;
 use maze_library::{
-    constants::constants::{WINDOW_HEIGHT, WINDOW_WIDTH}, environment::environment::Environment, exploring_algorithms::explore_handler::explore_maze_with, render_system::render::render::render_mazes, solving_algorithms::{dijkstra::dijkstra_graph, solve_handler::select_maze_solve_algorithm}
+    constants::constants::{WINDOW_HEIGHT, WINDOW_WIDTH}, environment::environment::Environment, environment_config::EnvConfig, exploring_algorithms::explore_handler::explore_maze_with, maze_gen::generator_registry::registered_generator_names, render_system::render::render::render_mazes, solving_algorithms::solve_handler::select_maze_solve_algorithm
 };
 mod cli;
+mod gen_stats;
 mod handler_functions;
 
 fn window_conf() -> Conf {
@@ -31,6 +34,11 @@ fn main() {
         Commands::Solve {
             explore_algoithm,
             gen_algotithm,
+            generation_parameters,
+            topology,
+            wrap,
+            levels,
+            level,
             solve_algoithm,
             count,
             width,
@@ -48,8 +56,11 @@ fn main() {
             );
             let mut environments = generate_environment_list(
                 &gen_algotithm,
-                width,
-                length,
+                &generation_parameters,
+                &EnvConfig::new_rust_config(width, length)
+                    .with_topology(topology)
+                    .with_wrap(wrap)
+                    .with_levels(levels),
                 count,
                 removed_walls,
                 Some(22),
@@ -63,12 +74,13 @@ fn main() {
             }
             macroquad::Window::from_config(window_conf(), async move {
                 // Game loop
-                render_mazes(environments, cell_size, false).await;
+                render_mazes(environments, cell_size, false, level).await;
             });
         }
         Commands::Display {
             count,
             filename,
+            level,
         } => {
             info!("Displaying maze from file...");
             info!("File Location: {}", filename);
@@ -82,12 +94,13 @@ fn main() {
             }
             macroquad::Window::from_config(window_conf(), async move {
                 // Game loop
-                render_mazes(environments, cell_size, false).await;
+                render_mazes(environments, cell_size, false, level).await;
             });
         }
         Commands::ColouredDisplay {
             count,
             filename,
+            level,
         } => {
             info!("Displaying maze from file...");
             info!("File Location: {}", filename);
@@ -101,11 +114,16 @@ fn main() {
             }
             macroquad::Window::from_config(window_conf(), async move {
                 // Game loop
-                render_mazes(environments, cell_size, true).await;
+                render_mazes(environments, cell_size, true, level).await;
             });
         }
         Commands::ShowGenBias {
             gen_algotithm,
+            generation_parameters,
+            topology,
+            wrap,
+            levels,
+            level,
             count,
             width,
             length,
@@ -114,15 +132,17 @@ fn main() {
             info!("Generating mazes...");
             let mut environments = generate_environment_list(
                 &gen_algotithm,
-                width,
-                length,
+                &generation_parameters,
+                &EnvConfig::new_rust_config(width, length)
+                    .with_topology(topology)
+                    .with_wrap(wrap)
+                    .with_levels(levels),
                 count,
                 removed_walls,
                 None,
             );
             environments.iter_mut().for_each(|env| {
-                env.weighted_graph = env.maze.convert_to_weighted_graph(None, false);
-                let path_graph = dijkstra_graph(&env, *env.maze.end.iter().next().unwrap())
+                let path_graph = dijkstra_map_from_end(env)
                     .into_iter()
                     .map(|(k, v)| (k, v.0)) // Take the first element (usize) from the tuple
                     .collect();
@@ -131,8 +151,61 @@ fn main() {
 
             macroquad::Window::from_config(window_conf(), async move {
                 // Game loop
-                render_mazes(environments, cell_size, true).await;
+                render_mazes(environments, cell_size, true, level).await;
             });
         }
+        Commands::GenStats {
+            gen_algotithms,
+            generation_parameters,
+            topology,
+            wrap,
+            levels,
+            count,
+            width,
+            length,
+            removed_walls,
+            seed,
+            format,
+            output,
+        } => {
+            let generators = if gen_algotithms.is_empty() {
+                registered_generator_names()
+            } else {
+                gen_algotithms
+            };
+            let mut summaries = vec![];
+            for generator in generators.iter() {
+                info!("Generating {} {} mazes...", count, generator);
+                let mut environments = generate_environment_list(
+                    generator,
+                    &generation_parameters,
+                    &EnvConfig::new_rust_config(width, length)
+                        .with_topology(topology)
+                        .with_wrap(wrap)
+                    .with_levels(levels),
+                    count,
+                    removed_walls,
+                    seed,
+                );
+                let samples: Vec<Vec<(String, f64)>> = environments
+                    .iter_mut()
+                    .map(|env| {
+                        let distance_map = dijkstra_map_from_end(env);
+                        maze_metrics(env, &distance_map)
+                    })
+                    .collect();
+                summaries.push(summarise(generator, &samples));
+            }
+            let report = match format {
+                StatsFormat::Table => format_table(&summaries),
+                StatsFormat::Json => format_json(&summaries),
+                StatsFormat::Csv => format_csv(&summaries),
+            };
+            match output {
+                Some(filename) => std::fs::write(&filename, report)
+                    .unwrap_or_else(|e| panic!("Could not write {}: {}", filename, e)),
+                None => println!("{}", report),
+            }
+        }
     }
 }
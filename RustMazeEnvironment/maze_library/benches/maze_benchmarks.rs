@@ -0,0 +1,49 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use maze_library::{
+    direction::Direction,
+    environment::environment::Environment,
+    environment_config::EnvConfig,
+    maze_gen::maze_gen_handler::{select_maze_algorithm, GenerationParameters, MazeType},
+};
+
+fn generated_environment(size: usize) -> Environment {
+    let mut env = Environment::new_with_seed(EnvConfig::new_rust_config(size, size), Some(1));
+    let walls = select_maze_algorithm(
+        &env.maze,
+        Some(1),
+        &MazeType::Kruzkals,
+        &GenerationParameters::default(),
+    );
+    env.maze.break_walls_for_path(walls);
+    env.weighted_graph = env.maze.convert_to_weighted_graph(None, true);
+    env
+}
+
+fn bench_convert_to_weighted_graph(c: &mut Criterion) {
+    let env = generated_environment(31);
+    c.bench_function("convert_to_weighted_graph 31x31", |b| {
+        b.iter(|| black_box(env.maze.convert_to_weighted_graph(None, true)))
+    });
+}
+
+/// The rust side of take_action, observations need the python feature so are left out
+fn bench_take_action_moves(c: &mut Criterion) {
+    let env = generated_environment(31);
+    c.bench_function("take_action moves 31x31", |b| {
+        b.iter_batched(
+            || env.clone(),
+            |mut env| {
+                for step in 0..200 {
+                    let direction = Direction::from(step % 4);
+                    black_box(env.move_from_current(&direction, 0));
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, bench_convert_to_weighted_graph, bench_take_action_moves);
+criterion_main!(benches);
@@ -2,4 +2,8 @@ pub mod constants {
     pub const WINDOW_WIDTH: i32 = 450;
     pub const WINDOW_HEIGHT: i32 = 450;
     pub const NUMBER_OF_INPUT_FEATURES : usize = 198;
+    //held keys, a locked door each way and a door and key flag for each 5x5 cell
+    pub const DOOR_INPUT_FEATURES : usize = 1 + 4 + 5 * 5 * 2;
+    //the cost of each 5x5 cell against the dearest cell in the maze
+    pub const TERRAIN_INPUT_FEATURES : usize = 5 * 5;
 }
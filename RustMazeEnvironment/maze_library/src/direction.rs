@@ -13,15 +13,27 @@ pub enum Direction {
     East = 1,
     South = 2,
     West = 3,
+    /// up and right on a hex grid, only hex cells have this side
+    NorthEast = 4,
+    /// down and left on a hex grid, only hex cells have this side
+    SouthWest = 5,
+    /// the stairs to the level above, only cells of a layered maze have this side
+    Up = 6,
+    /// the stairs to the level below, only cells of a layered maze have this side
+    Down = 7,
 }
 
 impl From<usize> for Direction {
     fn from(value: usize) -> Self {
-        match value % 4 {
+        match value % 8 {
             0 => Direction::North,
             1 => Direction::East,
             2 => Direction::South,
-            _ => Direction::West, // covers 3 and ensures safety
+            3 => Direction::West,
+            4 => Direction::NorthEast,
+            5 => Direction::SouthWest,
+            6 => Direction::Up,
+            _ => Direction::Down, // covers 7 and ensures safety
         }
     }
 }
@@ -33,6 +45,10 @@ impl fmt::Display for Direction {
             Direction::South => "South",
             Direction::East => "East",
             Direction::West => "West",
+            Direction::NorthEast => "NorthEast",
+            Direction::SouthWest => "SouthWest",
+            Direction::Up => "Up",
+            Direction::Down => "Down",
         };
         write!(f, "{}", direction_str)
     }
@@ -46,6 +62,8 @@ pub fn direction_between(from: Coordinate, to: Coordinate) -> Option<Direction>
         (0, d) if d < 0 => Some(Direction::North),
         (d, 0) if d > 0 => Some(Direction::East),
         (d, 0) if d < 0 => Some(Direction::West),
+        (d, e) if d > 0 && e == -d => Some(Direction::NorthEast),
+        (d, e) if d < 0 && e == -d => Some(Direction::SouthWest),
         _ => None, // not adjacent or diagonal movement
     }
 }
@@ -62,6 +80,10 @@ impl Direction {
             Direction::South => Direction::North,
             Direction::East => Direction::West,
             Direction::West => Direction::East,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::SouthWest => Direction::NorthEast,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
         }
     }
 
@@ -76,11 +98,66 @@ impl Direction {
     }
 
     pub fn turn_amount(&self, prev_direction: &Direction) -> usize {
+        if self.is_vertical() || prev_direction.is_vertical() {
+            //taking the stairs is a turn off any corridor, coming back down them a reverse
+            return if self == prev_direction {
+                0
+            } else if *self == prev_direction.opposite_direction() {
+                2
+            } else {
+                1
+            };
+        }
+        if self.is_diagonal() || prev_direction.is_diagonal() {
+            //around the six hex sides a reverse is three steps, anything short of it one turn
+            let steps = self.hex_position().abs_diff(prev_direction.hex_position());
+            return match steps.min(6 - steps) {
+                0 => 0,
+                3 => 2,
+                _ => 1,
+            };
+        }
         ((*prev_direction as i32 - *self as i32).abs() % 3) as usize
     }
+
+    pub fn is_diagonal(&self) -> bool {
+        matches!(self, Direction::NorthEast | Direction::SouthWest)
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        matches!(self, Direction::Up | Direction::Down)
+    }
 }
 
 impl Direction {
+    //clockwise position around a hex cell
+    fn hex_position(&self) -> usize {
+        match self {
+            Direction::NorthEast => 0,
+            Direction::East => 1,
+            Direction::South => 2,
+            Direction::SouthWest => 3,
+            Direction::West => 4,
+            Direction::North => 5,
+            //never reached, vertical turns are counted before looking round the hex
+            Direction::Up | Direction::Down => 0,
+        }
+    }
+
+    /// The change in (x, y) for one step this way, nothing for the stairs as how far a level
+    /// is depends on the maze, see `Maze::move_from`
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthEast => (1, -1),
+            Direction::SouthWest => (-1, 1),
+            Direction::Up | Direction::Down => (0, 0),
+        }
+    }
+
     pub fn random(rng: &mut StdRng) -> Direction {
         match rng.random_range(0..4) {
             0 => Direction::North,
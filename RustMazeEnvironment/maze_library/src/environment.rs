@@ -2,12 +2,25 @@ pub mod environment {
     use crate::{
         direction::{direction_between, Direction},
         environment_config::EnvConfig,
+        goals::GoalProgress,
         map_vec_conversion::map_vec_conversion,
-        maze::maze::Maze,
+        maze::maze::{Maze, MoveError},
+        maze_gen::{
+            braid::braid_dead_ends,
+            generator_registry::{find_generator, run_generator},
+            doors::place_doors_and_keys,
+            mask::MaskShape,
+            portals::place_portals,
+            terrain::generate_terrain,
+            weave::place_crossings,
+            maze_gen_handler::GenerationParameters,
+            placement::{add_random_goals, place_start_and_goal},
+        },
+        rng_stream::RngStream,
     };
     use log::error;
     use serde::{Deserialize, Serialize};
-    use std::collections::HashMap;
+    use std::collections::{BTreeSet, HashMap};
     pub type Coordinate = (usize, usize);
 
     #[cfg_attr(feature = "python", pyo3::pyclass)]
@@ -26,20 +39,56 @@ pub mod environment {
         pub overall_visited: HashMap<Coordinate, usize>,
         #[serde(skip)]
         pub weighted_graph: HashMap<Coordinate, HashMap<Direction, usize>>,
+        /// every maze regeneration and imperfection draws from this so seeded runs replay exactly
+        #[serde(default)]
+        pub rng: RngStream,
+        /// ids of the keys picked up since the run started
+        #[serde(default)]
+        pub keys_held: BTreeSet<usize>,
+    }
+
+    /// The direction taken from `from` to reach `to`, following a graph edge through a portal
+    /// when the two cells are not in line. On a wrapped maze two edges can reach the same cell
+    /// and the shorter one is taken
+    pub fn graph_edge_direction(
+        maze: &Maze,
+        weighted_graph: &HashMap<Coordinate, HashMap<Direction, usize>>,
+        from: Coordinate,
+        to: Coordinate,
+    ) -> Option<Direction> {
+        let edges = weighted_graph.get(&from);
+        maze.topology
+            .directions()
+            .iter()
+            .filter_map(|direction| {
+                let steps = *edges?.get(direction)?;
+                (maze.destination(direction, &from, steps) == Ok(to)).then_some((steps, *direction))
+            })
+            .min_by_key(|(steps, _)| *steps)
+            .map(|(_, direction)| direction)
+            .or_else(|| maze.direction_to(from, to))
+            .or_else(|| direction_between(from, to))
     }
 
     pub fn calcualte_score_for_coordinate_vector(
         path: &Vec<Coordinate>,
         weighted_graph: &HashMap<Coordinate, HashMap<Direction, usize>>,
+        maze: &Maze,
     ) -> (usize, usize, usize) {
         let mut direction_map = HashMap::new();
         let mut reverse_count = 0;
         let mut hit_count = 0;
-        let mut prev_direction = direction_between(path[0], path[1]).unwrap_or(Direction::North);
+        let mut prev_direction = graph_edge_direction(maze, weighted_graph, path[0], path[1])
+            .unwrap_or(Direction::North);
         let mut total_run_steps = 0;
         let mut total_run_penalty = 0;
         for index in 2..path.len() {
-            let direction = direction_between(path[index - 1], path[index]);
+            //the hop out of a portal is free and keeps the heading
+            if maze.portal_partner(path[index - 1]) == Some(path[index]) {
+                continue;
+            }
+            let direction =
+                graph_edge_direction(maze, weighted_graph, path[index - 1], path[index]);
 
             if direction.is_none() {
                 hit_count += 1;
@@ -64,7 +113,7 @@ pub mod environment {
             total_run_penalty += turn_penalty;
 
             total_run_steps += match count_steps.expect("Hashmap get").get(&direction.unwrap()) {
-                Some(steps) => steps,
+                Some(steps) => maze.edge_cost(direction.as_ref().unwrap(), &path[index - 1], *steps),
                 None => {
                     panic!("ERROR");
                 }
@@ -79,9 +128,29 @@ pub mod environment {
         )
     }
 
+    //a fully walled maze in the configured topology with `height` rows on each level, left
+    //unwrapped if it is too small to wrap and on one level if it cannot be layered
+    fn blank_maze(width: usize, height: usize, config: &EnvConfig) -> Maze {
+        let mut maze =
+            Maze::init_maze_with_topology(width, height * config.levels, config.topology);
+        if let Err(e) = maze.set_wrap(config.wrap) {
+            error!("Could not wrap the maze: {:?}", e);
+        }
+        if let Err(e) = maze.set_levels(config.levels) {
+            error!("Could not layer the maze: {:?}", e);
+            maze = Maze::init_maze_with_topology(width, height, config.topology);
+            let _ = maze.set_wrap(config.wrap);
+        }
+        maze
+    }
+
     impl Environment {
         pub fn new(env_config: EnvConfig) -> Environment {
-            let maze = Maze::init_maze(env_config.maze_width, env_config.maze_height);
+            Environment::new_with_seed(env_config, None)
+        }
+
+        pub fn new_with_seed(env_config: EnvConfig, rng_seed: Option<u64>) -> Environment {
+            let maze = blank_maze(env_config.maze_width, env_config.maze_height, &env_config);
             Environment {
                 current_location: maze.start,
                 previous_direction: None,
@@ -93,22 +162,19 @@ pub mod environment {
                 steps: 0,
                 total_steps: 0,
                 weighted_graph: HashMap::new(),
+                rng: RngStream::new(rng_seed),
+                keys_held: BTreeSet::new(),
             }
         }
     }
 
     impl Environment {
         pub fn mark_nearby_as_visited(&mut self) {
-            for direction in [
-                Direction::North,
-                Direction::South,
-                Direction::East,
-                Direction::West,
-            ] {
+            for direction in self.maze.sides(self.current_location) {
                 let new_coords =
                     match self
                         .maze
-                        .move_from_with_walls(&direction, &self.current_location, 1)
+                        .move_from_with_walls(direction, &self.current_location, 1)
                     {
                         Ok(new) => new,
                         Err(_) => continue,
@@ -126,6 +192,12 @@ pub mod environment {
                 .get(&self.current_location)
                 .and_then(|inner_map| inner_map.get(direction))
                 .unwrap_or(&0);
+            //a locked door turns the agent back like a wall
+            let steps = if self.door_locked(self.current_location, *direction) {
+                0
+            } else {
+                steps
+            };
 
             if steps == 0 {
                 self.steps += 1;
@@ -155,17 +227,48 @@ pub mod environment {
                 *self.visited.entry(intermediary_step).or_insert(0) += 1;
                 *self.overall_visited.entry(intermediary_step).or_insert(0) += 1;
                 self.current_location = intermediary_step;
+                if let Some(key) = self.maze.key_at(intermediary_step) {
+                    self.keys_held.insert(key);
+                }
+                if let Some(partner) = self.maze.portal_partner(intermediary_step) {
+                    self.path_followed.push((partner, run));
+                    *self.visited.entry(partner).or_insert(0) += 1;
+                    *self.overall_visited.entry(partner).or_insert(0) += 1;
+                    self.current_location = partner;
+                    break;
+                }
             }
             self.mark_nearby_as_visited();
             self.previous_direction = Some(*direction);
             steps
         }
 
+        /// Door features are only observed when the config asks for doors, keeping the input
+        /// shape fixed across regenerations
+        pub fn uses_doors(&self) -> bool {
+            self.config.python_config.generation_parameters.door_count > 0
+        }
+
+        /// Terrain costs are only observed when the config generates terrain
+        pub fn uses_terrain(&self) -> bool {
+            self.config.python_config.generation_parameters.terrain_max_cost > 1
+        }
+
+        pub fn door_locked(&self, coordinate: Coordinate, direction: Direction) -> bool {
+            self.maze
+                .door_between(coordinate, direction)
+                .is_some_and(|door| !self.keys_held.contains(&door.key))
+        }
+
+        /// Ways on from the current location, a locked door is left out like a wall
         pub fn available_paths(&self) -> HashMap<Direction, usize> {
             self.weighted_graph
                 .get(&self.current_location) // Option<&HashMap<Direction, usize>>
                 .map(|paths| paths.clone()) // Clone the inner HashMap
                 .unwrap_or_default()
+                .into_iter()
+                .filter(|(direction, _)| !self.door_locked(self.current_location, *direction))
+                .collect()
         }
 
         pub fn to_json(&self) -> String {
@@ -200,17 +303,141 @@ pub mod environment {
                 return (0, 0, 0, false);
             }
             let (total_run_score, hit_count, reverse_count) =
-                calcualte_score_for_coordinate_vector(&filtered_path, &self.weighted_graph);
+                calcualte_score_for_coordinate_vector(
+                    &filtered_path,
+                    &self.weighted_graph,
+                    &self.maze,
+                );
             (
                 total_run_score,
                 hit_count,
                 reverse_count,
-                self.maze
-                    .end
-                    .contains(&filtered_path[filtered_path.len() - 1]),
+                self.goal_progress(run_to_score).is_complete(),
+            )
+        }
+
+        /// Which goals the run has reached under the configured goal mode
+        pub fn goal_progress(&self, run: usize) -> GoalProgress {
+            GoalProgress::from_path(
+                self.config.goal_mode,
+                self.maze.goal_regions(),
+                self.path_followed
+                    .iter()
+                    .filter(|(_, path_run)| *path_run == run)
+                    .map(|(coordinate, _)| coordinate),
             )
         }
 
+        /// Centre of the nearest goal that still counts for the current run
+        pub fn goal_target(&self) -> (f32, f32) {
+            self.goal_progress(self.get_current_run())
+                .nearest_target_centre(self.current_location)
+                .unwrap_or_else(|| self.maze.goal_centre())
+        }
+
+        /// Builds a fresh maze with the named generator from the environment rng and resets the episode
+        pub fn regenerate_maze(
+            &mut self,
+            algorithm: &str,
+            parameters: &GenerationParameters,
+        ) -> Option<()> {
+            let generator = find_generator(algorithm)?;
+            let mut maze = blank_maze(self.maze.width, self.maze.level_height(), &self.config);
+            if let Some(shape) = &parameters.mask {
+                let mut rng = self.rng.next_rng();
+                let enabled = shape.build(&maze, &mut rng).ok()?;
+                maze.set_mask(enabled).ok()?;
+            }
+            if parameters.weave_density > 0.0 {
+                place_crossings(&mut maze, parameters.weave_density, &mut self.rng.next_rng());
+            }
+            let walls = run_generator(&*generator, &maze, self.rng.next_rng(), parameters);
+            maze.break_walls_for_path(walls);
+            self.maze = maze;
+            self.place_start_and_goal().ok()?;
+            if parameters.door_count > 0 {
+                self.add_doors_and_keys(parameters.door_count);
+            }
+            if parameters.portal_count > 0 {
+                self.add_portals(parameters.portal_count);
+            }
+            if parameters.terrain_max_cost > 1 {
+                self.add_terrain(parameters.terrain_max_cost, parameters.terrain_scale);
+            }
+            self.weighted_graph = self.maze.convert_to_weighted_graph(None, true);
+            self.steps = 0;
+            self.total_steps = 0;
+            Some(())
+        }
+
+        /// Moves the start and goals to where the config puts them and restarts the path
+        /// there, the weighted graph needs rebuilding afterwards
+        pub fn place_start_and_goal(&mut self) -> Result<(), MoveError> {
+            let mut rng = self.rng.next_rng();
+            place_start_and_goal(&mut self.maze, &self.config.placement, &mut rng)?;
+            add_random_goals(&mut self.maze, self.config.goal_count.saturating_sub(1), &mut rng);
+            self.restart_at_start();
+            Ok(())
+        }
+
+        /// Cuts the maze down to a mask shape and restarts the path, best done before carving
+        pub fn apply_mask(&mut self, shape: &MaskShape) -> Result<(), String> {
+            let mut rng = self.rng.next_rng();
+            let enabled = shape.build(&self.maze, &mut rng)?;
+            self.maze
+                .set_mask(enabled)
+                .map_err(|e| format!("mask does not fit the maze: {:?}", e))?;
+            self.restart_at_start();
+            Ok(())
+        }
+
+        fn restart_at_start(&mut self) {
+            let start = self.maze.get_starting_point();
+            self.current_location = start;
+            self.previous_direction = None;
+            self.visited = HashMap::from([(start, 1)]);
+            self.overall_visited = HashMap::from([(start, 1)]);
+            self.path_followed = Vec::from([(start, 0)]);
+            self.keys_held.clear();
+        }
+
+        /// Locks passages on the route to the goal and hides their keys before them
+        pub fn add_doors_and_keys(&mut self, count: usize) {
+            let mut rng = self.rng.next_rng();
+            place_doors_and_keys(&mut self.maze, count, &mut rng);
+            self.keys_held.clear();
+        }
+
+        /// Turns some cells of the unwalled maze into crossings for the generator to weave round
+        pub fn add_crossings(&mut self, density: f32) {
+            let mut rng = self.rng.next_rng();
+            place_crossings(&mut self.maze, density, &mut rng);
+        }
+
+        /// Joins random pairs of free cells with portals
+        pub fn add_portals(&mut self, count: usize) {
+            let mut rng = self.rng.next_rng();
+            place_portals(&mut self.maze, count, &mut rng);
+        }
+
+        /// Lays noise generated step costs from 1 to `max_cost` over the maze
+        pub fn add_terrain(&mut self, max_cost: u8, scale: usize) {
+            let mut rng = self.rng.next_rng();
+            generate_terrain(&mut self.maze, max_cost, scale, &mut rng);
+        }
+
+        pub fn break_random_walls(&mut self, amount: usize) {
+            let mut rng = self.rng.next_rng();
+            let walls_to_break = self.maze.break_random_walls(amount, &mut rng);
+            self.maze.break_walls_for_path(walls_to_break);
+        }
+
+        pub fn braid_dead_ends(&mut self, fraction: f32) {
+            let mut rng = self.rng.next_rng();
+            let walls_to_break = braid_dead_ends(&self.maze, fraction, &mut rng);
+            self.maze.break_walls_for_path(walls_to_break);
+        }
+
         pub fn move_path_vec(&mut self, path: &Vec<(Coordinate, Direction)>, run: usize) {
             for (_, direction) in path {
                 self.move_from_current(direction, run);
@@ -221,35 +448,251 @@ pub mod environment {
     #[cfg(test)]
     mod tests {
 
-        use crate::environment_config::PythonConfig;
+        use crate::{
+            environment_config::PythonConfig,
+            goals::GoalMode,
+            maze::maze::{Door, Key, Portal},
+            exploring_algorithms::explore_handler::{explore_maze_with, ExploreAlgorithm},
+            maze_gen::{mask::MaskShape, placement::Placement},
+            solving_algorithms::{
+                dijkstra::dijkstra_graph,
+                solve_handler::{select_maze_solve_algorithm, solve_goal_route, SolveAlgorithm},
+            },
+            topology::Topology,
+        };
 
         use super::*;
 
         #[test]
         fn test_json() {
-            let env = Environment::new(EnvConfig {
-                maze_width: 10,
-                maze_height: 10,
-                python_config: PythonConfig::default(),
-            });
+            let env = Environment::new(EnvConfig::new(10, 10, PythonConfig::default()));
             let json = env.to_json();
             let parsed = Environment::from_json(&json);
             assert!(parsed.is_ok());
         }
 
         #[test]
-        fn test_movement() {
-            let mut env = Environment::new(EnvConfig {
-                maze_width: 10,
-                maze_height: 10,
-                python_config: PythonConfig::default(),
+        fn test_default_config_regenerates_with_a_registered_generator() {
+            let config = EnvConfig::new_rust_config(8, 8);
+            let algorithm = config.python_config.generated_maze_type.clone();
+            let mut env = Environment::new_with_seed(config, Some(1));
+            assert!(env
+                .regenerate_maze(&algorithm, &GenerationParameters::default())
+                .is_some());
+        }
+
+        #[test]
+        fn test_regeneration_uses_configured_placement() {
+            let config =
+                EnvConfig::new_rust_config(12, 12).with_placement(Placement::FarthestPair);
+            let mut env = Environment::new_with_seed(config, Some(9));
+            env.regenerate_maze("Kruzkals", &GenerationParameters::default()).unwrap();
+
+            let goal = *env.maze.end.iter().next().unwrap();
+            let distances = env.maze.distances_from(env.maze.start);
+            let longest = distances.iter().flatten().max().unwrap();
+            assert_eq!(distances[goal.1 * 12 + goal.0], Some(*longest));
+            assert_eq!(env.current_location, env.maze.start);
+            assert!(env.weighted_graph.contains_key(&goal));
+            assert_eq!(env.maze.goal_centre(), (goal.0 as f32, goal.1 as f32));
+        }
+
+        #[test]
+        fn test_ordered_goals_are_solved_in_order() {
+            let config = EnvConfig::new_rust_config(10, 10).with_goals(GoalMode::Ordered, 1);
+            let mut env = Environment::new_with_seed(config, Some(2));
+            env.regenerate_maze("Kruzkals", &GenerationParameters::default()).unwrap();
+            env.maze.set_goals(vec![vec![(9, 0)], vec![(0, 0)]]);
+            env.weighted_graph = env.maze.convert_to_weighted_graph(None, true);
+
+            let route = solve_goal_route(&env, &SolveAlgorithm::Dijkstra);
+            let first = route.iter().position(|cell| *cell == (9, 0)).unwrap();
+            assert_eq!(route[route.len() - 1], (0, 0));
+            assert!(!route[..first].contains(&(0, 0)));
+
+            let path = select_maze_solve_algorithm(&env, &SolveAlgorithm::Dijkstra);
+            env.move_path_vec(&path, 0);
+            assert!(env.calculate_run_score(0).3);
+            assert_eq!(env.goal_progress(0).reached_count(), 2);
+        }
+
+        #[test]
+        fn test_doors_stay_shut_until_their_key_is_held() {
+            let mut env = Environment::new(EnvConfig::new(10, 10, PythonConfig::default()));
+            env.maze.break_walls_for_path(vec![
+                ((0, 9), Direction::East),
+                ((1, 9), Direction::East),
+                ((0, 9), Direction::North),
+            ]);
+            env.maze.doors.push(Door {
+                coordinate: (1, 9),
+                direction: Direction::East,
+                key: 0,
+            });
+            env.maze.keys.push(Key {
+                coordinate: (0, 8),
+                id: 0,
             });
+            env.weighted_graph = env.maze.convert_to_weighted_graph(None, true);
+
+            assert_eq!(env.move_from_current(&Direction::East, 0), 1);
+            assert_eq!(env.move_from_current(&Direction::East, 0), 0);
+            assert_eq!(env.current_location, (1, 9));
+
+            env.move_from_current(&Direction::West, 0);
+            env.move_from_current(&Direction::North, 0);
+            assert!(env.keys_held.contains(&0));
+            env.move_from_current(&Direction::South, 0);
+            env.move_from_current(&Direction::East, 0);
+            assert_eq!(env.move_from_current(&Direction::East, 0), 1);
+            assert_eq!(env.current_location, (2, 9));
+        }
+
+        #[test]
+        fn test_generated_door_mazes_are_solved_by_fetching_the_keys() {
+            let parameters = GenerationParameters {
+                door_count: 3,
+                ..GenerationParameters::default()
+            };
+            for seed in 0..10 {
+                for algorithm in [SolveAlgorithm::Dijkstra, SolveAlgorithm::Dfs] {
+                    let config = EnvConfig::new_rust_config(10, 10);
+                    let mut env = Environment::new_with_seed(config, Some(seed));
+                    env.regenerate_maze("Kruzkals", &parameters).unwrap();
+                    assert!(!env.maze.doors.is_empty());
+
+                    let path = select_maze_solve_algorithm(&env, &algorithm);
+                    env.move_path_vec(&path, 0);
+                    assert!(env.goal_progress(0).is_complete(), "seed {}", seed);
+                    assert_eq!(env.keys_held.len(), env.maze.doors.len());
+                }
+            }
+        }
+
+        #[test]
+        fn test_wall_following_treats_locked_doors_as_walls() {
+            let parameters = GenerationParameters {
+                door_count: 3,
+                ..GenerationParameters::default()
+            };
+            let config = EnvConfig::new_rust_config(10, 10);
+            let mut env = Environment::new_with_seed(config, Some(0));
+            env.regenerate_maze("Kruzkals", &parameters).unwrap();
+            let door = env.maze.doors[0];
+            env.current_location = door.coordinate;
+            assert!(!env.available_paths().contains_key(&door.direction));
+
+            env.current_location = env.maze.start;
+            explore_maze_with(&mut env, &ExploreAlgorithm::WallFollowing);
+            assert!(env.goal_progress(env.get_current_run()).is_complete());
+            assert_eq!(env.keys_held.len(), env.maze.doors.len());
+        }
+
+        #[test]
+        fn test_portals_carry_moves_and_routes_to_their_partner() {
+            let mut env = Environment::new(EnvConfig::new(10, 10, PythonConfig::default()));
+            env.maze.set_end((5, 5));
+            env.maze.break_walls_for_path(vec![
+                ((0, 9), Direction::East),
+                ((5, 4), Direction::South),
+            ]);
+            env.maze.portals.push(Portal {
+                a: (1, 9),
+                b: (5, 4),
+            });
+            env.weighted_graph = env.maze.convert_to_weighted_graph(None, true);
+
+            assert_eq!(
+                solve_goal_route(&env, &SolveAlgorithm::Dijkstra),
+                vec![(0, 9), (5, 4), (5, 5)]
+            );
+            env.move_from_current(&Direction::East, 0);
+            assert_eq!(env.current_location, (5, 4));
+            env.move_from_current(&Direction::South, 0);
+            let (steps, _, _, solved) = env.calculate_run_score(0);
+            assert!(solved);
+            assert_eq!(steps, 2);
+        }
+
+        #[test]
+        fn test_dijkstra_takes_the_cheapest_route_over_the_shortest() {
+            let mut env = Environment::new(EnvConfig::new(10, 10, PythonConfig::default()));
+            env.maze.set_end((2, 9));
+            env.maze.break_walls_for_path(vec![
+                ((0, 9), Direction::East),
+                ((1, 9), Direction::East),
+                ((0, 9), Direction::North),
+                ((0, 8), Direction::East),
+                ((1, 8), Direction::East),
+                ((2, 8), Direction::South),
+            ]);
+            env.maze.set_cost((1, 9), 9);
+            env.weighted_graph = env.maze.convert_to_weighted_graph(None, true);
+
+            let route = select_maze_solve_algorithm(&env, &SolveAlgorithm::Dijkstra);
+            assert_eq!(route[0], ((0, 9), Direction::North));
+            env.move_path_vec(&route, 0);
+            assert_eq!(env.current_location, (2, 9));
+        }
+
+        #[test]
+        fn test_dijkstra_graph_measures_from_the_nearest_start() {
+            let mut env = Environment::new(EnvConfig::new(5, 5, PythonConfig::default()));
+            env.maze
+                .break_walls_for_path((0..4).map(|x| ((x, 4), Direction::East)).collect());
+            env.weighted_graph = env.maze.convert_to_weighted_graph(None, false);
+
+            let path_map = dijkstra_graph(&env, &[(0, 4), (4, 4)]);
+            assert_eq!(path_map[&(1, 4)], (1, (0, 4)));
+            assert_eq!(path_map[&(3, 4)], (1, (4, 4)));
+            assert_eq!(path_map[&(2, 4)].0, 2);
+        }
+
+        #[test]
+        fn test_masked_regeneration_keeps_the_task_inside_the_mask() {
+            let config =
+                EnvConfig::new_rust_config(12, 12).with_placement(Placement::OppositeCorners);
+            let mut env = Environment::new_with_seed(config, Some(2));
+            let parameters = GenerationParameters {
+                mask: Some(MaskShape::Circle),
+                ..GenerationParameters::default()
+            };
+            env.regenerate_maze("Sidewinder", &parameters).unwrap();
+
+            assert!(env.maze.is_enabled(env.maze.start));
+            assert!(env.maze.end.iter().all(|end| env.maze.is_enabled(*end)));
+            assert!(!env.maze.is_enabled((0, 0)));
+            assert_eq!(env.current_location, env.maze.start);
+            assert!(!solve_goal_route(&env, &SolveAlgorithm::Dijkstra).is_empty());
+        }
+
+        #[test]
+        fn test_seeded_regeneration_replays_after_json() {
+            let parameters = GenerationParameters::default();
+            let config = EnvConfig::new_rust_config(10, 10);
+            let mut env = Environment::new_with_seed(config.clone(), Some(4));
+            let mut replay = Environment::new_with_seed(config, Some(4));
+
+            env.regenerate_maze("Wilsons", &parameters).unwrap();
+            replay.regenerate_maze("Wilsons", &parameters).unwrap();
+            assert_eq!(env.maze.grid, replay.maze.grid);
+
+            let mut reloaded = Environment::from_json(&env.to_json()).unwrap();
+            env.regenerate_maze("Kruzkals", &parameters).unwrap();
+            env.break_random_walls(5);
+            reloaded.regenerate_maze("Kruzkals", &parameters).unwrap();
+            reloaded.break_random_walls(5);
+            assert_eq!(env.maze.grid, reloaded.maze.grid);
+            assert_eq!(env.rng, reloaded.rng);
+        }
+
+        #[test]
+        fn test_movement() {
+            let mut env = Environment::new(EnvConfig::new(10, 10, PythonConfig::default()));
 
             // Allow eastward movement for 2 steps
-            env.maze.grid[0][9].walls.remove(&Direction::East);
-            env.maze.grid[1][9].walls.remove(&Direction::West);
-            env.maze.grid[1][9].walls.remove(&Direction::East);
-            env.maze.grid[2][9].walls.remove(&Direction::West);
+            env.maze.open_wall((0, 9), Direction::East).unwrap();
+            env.maze.open_wall((1, 9), Direction::East).unwrap();
 
             // Update agent's weighted graph to allow 2 eastward steps
             let mut east_map = HashMap::new();
@@ -263,5 +706,65 @@ pub mod environment {
             assert_eq!(env.current_location, (2, 9));
             assert_eq!(env.path_followed.len(), 3);
         }
+
+        #[test]
+        fn test_hex_moves_run_along_diagonal_corridors() {
+            let config = EnvConfig::new_rust_config(6, 6).with_topology(Topology::Hex);
+            let mut env = Environment::new(config);
+            env.maze.open_wall((0, 5), Direction::NorthEast).unwrap();
+            env.maze.open_wall((1, 4), Direction::NorthEast).unwrap();
+            env.weighted_graph = env.maze.convert_to_weighted_graph(None, true);
+            env.current_location = (0, 5);
+
+            assert_eq!(env.move_from_current(&Direction::NorthEast, 0), 2);
+            assert_eq!(env.current_location, (2, 3));
+            assert_eq!(
+                env.maze.move_from(&Direction::NorthEast, &(5, 0), 1),
+                Err(MoveError::OutOfBounds)
+            );
+        }
+
+        #[test]
+        fn test_weave_mazes_explore_and_solve_through_tunnels() {
+            let parameters = GenerationParameters {
+                weave_density: 0.6,
+                ..GenerationParameters::default()
+            };
+            let mut env = Environment::new_with_seed(EnvConfig::new_rust_config(9, 9), Some(4));
+            env.regenerate_maze("Kruzkals", &parameters).unwrap();
+            assert!(!env.maze.crossings.is_empty());
+            explore_maze_with(&mut env, &ExploreAlgorithm::WallFollowing);
+
+            let run = env.get_current_run() + 1;
+            let path = select_maze_solve_algorithm(&env, &SolveAlgorithm::Dijkstra);
+            env.move_path_vec(&path, run);
+            assert!(env.goal_progress(run).is_complete());
+        }
+
+        #[test]
+        fn test_other_grids_explore_and_solve() {
+            for (topology, wrap, levels) in [
+                (Topology::Hex, false, 1),
+                (Topology::Triangle, false, 1),
+                (Topology::Square, true, 1),
+                (Topology::Hex, true, 1),
+                (Topology::Square, false, 3),
+            ] {
+                let config = EnvConfig::new_rust_config(8, 6)
+                    .with_topology(topology)
+                    .with_wrap(wrap)
+                    .with_levels(levels);
+                let mut env = Environment::new_with_seed(config, Some(9));
+                env.regenerate_maze("Wilsons", &GenerationParameters::default()).unwrap();
+                assert_eq!(env.maze.is_wrapped(), wrap);
+                assert_eq!(env.maze.levels(), levels);
+                explore_maze_with(&mut env, &ExploreAlgorithm::WallFollowing);
+
+                let run = env.get_current_run() + 1;
+                let path = select_maze_solve_algorithm(&env, &SolveAlgorithm::Dijkstra);
+                env.move_path_vec(&path, run);
+                assert!(env.goal_progress(run).is_complete(), "{}", topology);
+            }
+        }
     }
 }
@@ -1,12 +1,44 @@
 use serde::{Deserialize, Serialize};
 
-use crate::maze_gen::maze_gen_handler::MazeType;
+use crate::{
+    goals::GoalMode,
+    maze_gen::{
+        maze_gen_handler::{GenerationParameters, MazeType},
+        placement::Placement,
+    },
+    topology::Topology,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnvConfig {
     pub maze_width: usize,
     pub maze_height: usize,
     pub python_config: PythonConfig,
+    /// where the start and goal go after each maze is generated
+    #[serde(default)]
+    pub placement: Placement,
+    #[serde(default)]
+    pub goal_mode: GoalMode,
+    /// goal regions placed, the placement picks the first and the rest are random cells
+    #[serde(default = "default_goal_count")]
+    pub goal_count: usize,
+    /// the cell shape mazes are built from
+    #[serde(default)]
+    pub topology: Topology,
+    /// whether mazes wrap round from each edge to the opposite one
+    #[serde(default)]
+    pub wrap: bool,
+    /// floors stacked up by stairs, each `maze_width` by `maze_height`
+    #[serde(default = "default_levels")]
+    pub levels: usize,
+}
+
+fn default_goal_count() -> usize {
+    1
+}
+
+fn default_levels() -> usize {
+    1
 }
 
 impl EnvConfig {
@@ -15,6 +47,12 @@ impl EnvConfig {
             maze_width,
             maze_height,
             python_config,
+            placement: Placement::default(),
+            goal_mode: GoalMode::default(),
+            goal_count: default_goal_count(),
+            topology: Topology::default(),
+            wrap: false,
+            levels: default_levels(),
         }
     }
 
@@ -23,16 +61,70 @@ impl EnvConfig {
             maze_width,
             maze_height,
             python_config: PythonConfig::default(),
+            placement: Placement::default(),
+            goal_mode: GoalMode::default(),
+            goal_count: default_goal_count(),
+            topology: Topology::default(),
+            wrap: false,
+            levels: default_levels(),
         }
     }
+
+    pub fn with_placement(mut self, placement: Placement) -> EnvConfig {
+        self.placement = placement;
+        self
+    }
+
+    pub fn with_goals(mut self, goal_mode: GoalMode, goal_count: usize) -> EnvConfig {
+        self.goal_mode = goal_mode;
+        self.goal_count = goal_count.max(1);
+        self
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> EnvConfig {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: bool) -> EnvConfig {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn with_levels(mut self, levels: usize) -> EnvConfig {
+        self.levels = levels.max(1);
+        self
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PythonConfig {
     pub allowed_revisits: usize,
     pub use_sparse_rewards: bool,
     pub mini_explore_runs_per_episode: usize,
     pub mini_exploit_runs_per_episode: usize,
     pub exploration_steps: usize,
-    pub generated_maze_type: MazeType
+    /// name of a registered maze generator
+    #[serde(default = "default_maze_type")]
+    pub generated_maze_type: String,
+    #[serde(default)]
+    pub generation_parameters: GenerationParameters,
+}
+
+fn default_maze_type() -> String {
+    MazeType::default().to_string()
+}
+
+impl Default for PythonConfig {
+    fn default() -> Self {
+        PythonConfig {
+            allowed_revisits: 0,
+            use_sparse_rewards: false,
+            mini_explore_runs_per_episode: 0,
+            mini_exploit_runs_per_episode: 0,
+            exploration_steps: 0,
+            generated_maze_type: default_maze_type(),
+            generation_parameters: GenerationParameters::default(),
+        }
+    }
 }
@@ -3,7 +3,10 @@ use std::str::FromStr;
 use clap::ValueEnum;
 use strum_macros::EnumIter;
 
-use crate::environment::environment::Environment;
+use crate::{
+    environment::environment::Environment,
+    goals::{GoalMode, GoalProgress},
+};
 
 use super::wall_following::follow_wall_explore;
 
@@ -28,7 +31,13 @@ impl FromStr for ExploreAlgorithm {
 pub fn explore_maze_with(environment: &mut Environment, algorithm: &ExploreAlgorithm) {
     match algorithm {
         ExploreAlgorithm::WallFollowing => {
-            follow_wall_explore(environment, *environment.maze.end.iter().next().unwrap());
+            //exploring only has to find every goal, the order is for solving
+            let mode = match environment.config.goal_mode {
+                GoalMode::Any => GoalMode::Any,
+                GoalMode::All | GoalMode::Ordered => GoalMode::All,
+            };
+            let goals = GoalProgress::new(mode, environment.maze.goal_regions());
+            follow_wall_explore(environment, goals);
         }
 
         ExploreAlgorithm::None => {
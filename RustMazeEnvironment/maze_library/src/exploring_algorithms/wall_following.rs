@@ -1,16 +1,14 @@
 use std::collections::HashSet;
 
-use crate::{direction::Direction, environment::environment::{Coordinate, Environment}};
+use crate::{direction::Direction, environment::environment::Environment, goals::GoalProgress};
 
 
-pub fn follow_wall_explore(env: &mut Environment, end: Coordinate) {
+/// Follows the wall until the goals have been reached and it is back at the start
+pub fn follow_wall_explore(env: &mut Environment, mut goals: GoalProgress) {
     let start = env.maze.start;
-    let mut has_not_reached_end = true;
     let run = env.get_current_run() + 1;
-    while env.current_location != start || has_not_reached_end {
-        if env.current_location == end {
-            has_not_reached_end = false;
-        }
+    while env.current_location != start || !goals.is_complete() {
+        goals.visit(env.current_location);
         let mut directions: HashSet<Direction> =
             env.available_paths().iter().map(|(dir, _)| *dir).collect();
         if env.previous_direction.is_some() {
@@ -20,18 +18,14 @@ pub fn follow_wall_explore(env: &mut Environment, end: Coordinate) {
             env.move_from_current(&env.previous_direction.unwrap().opposite_direction(), run);
             continue;
         }
-        for direction in [
-            Direction::East,
-            Direction::North,
-            Direction::West,
-            Direction::South,
-        ] {
-            let dir =
-                direction.relative_direction(&env.previous_direction.unwrap_or(Direction::North));
-            if directions.contains(&dir) {
-                env.move_from_current(&dir, run);
-                break;
-            }
-        }
+        //sweeping anticlockwise from where it came in tries right, then ahead, then left
+        let topology = env.maze.topology;
+        let heading = env.previous_direction.unwrap_or(Direction::North);
+        let back = topology.angle(heading.opposite_direction());
+        let dir = *directions
+            .iter()
+            .min_by_key(|dir| (back + 360 - topology.angle(**dir)) % 360)
+            .expect("checked there is a way on");
+        env.move_from_current(&dir, run);
     }
 }
@@ -0,0 +1,179 @@
+use std::{collections::HashSet, str::FromStr};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use crate::environment::environment::Coordinate;
+
+/// What counts as solving a maze with several goal regions
+#[derive(
+    ValueEnum,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Hash,
+    Eq,
+    PartialEq,
+    EnumIter,
+    Serialize,
+    Deserialize,
+    Display,
+)]
+pub enum GoalMode {
+    /// reaching any one region
+    #[default]
+    Any,
+    /// reaching every region in any order
+    All,
+    /// reaching every region in the order they were given
+    Ordered,
+}
+
+impl FromStr for GoalMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "any" => Ok(GoalMode::Any),
+            "all" => Ok(GoalMode::All),
+            "ordered" => Ok(GoalMode::Ordered),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Which goal regions a run has reached so far under a goal mode
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalProgress {
+    mode: GoalMode,
+    regions: Vec<Vec<Coordinate>>,
+    reached: Vec<bool>,
+}
+
+impl GoalProgress {
+    pub fn new(mode: GoalMode, regions: Vec<Vec<Coordinate>>) -> GoalProgress {
+        GoalProgress {
+            mode,
+            reached: vec![false; regions.len()],
+            regions,
+        }
+    }
+
+    /// Progress after walking `path` from a fresh start
+    pub fn from_path<'a>(
+        mode: GoalMode,
+        regions: Vec<Vec<Coordinate>>,
+        path: impl IntoIterator<Item = &'a Coordinate>,
+    ) -> GoalProgress {
+        let mut progress = GoalProgress::new(mode, regions);
+        for coordinate in path {
+            progress.visit(*coordinate);
+        }
+        progress
+    }
+
+    /// Marks the region under `coordinate` as reached if it counts yet, true when it is newly reached
+    pub fn visit(&mut self, coordinate: Coordinate) -> bool {
+        match self
+            .target_regions()
+            .into_iter()
+            .find(|index| self.regions[*index].contains(&coordinate))
+        {
+            Some(index) => {
+                self.reached[index] = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn reached_count(&self) -> usize {
+        self.reached.iter().filter(|reached| **reached).count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        match self.mode {
+            GoalMode::Any => self.reached_count() > 0,
+            GoalMode::All | GoalMode::Ordered => {
+                !self.regions.is_empty() && self.reached_count() == self.regions.len()
+            }
+        }
+    }
+
+    //indexes of the regions that would count if reached next
+    fn target_regions(&self) -> Vec<usize> {
+        if self.is_complete() {
+            return vec![];
+        }
+        let unreached = (0..self.regions.len()).filter(|index| !self.reached[*index]);
+        match self.mode {
+            GoalMode::Any | GoalMode::All => unreached.collect(),
+            GoalMode::Ordered => unreached.take(1).collect(),
+        }
+    }
+
+    /// Every cell of the regions that would count if reached next
+    pub fn target_cells(&self) -> HashSet<Coordinate> {
+        self.target_regions()
+            .into_iter()
+            .flat_map(|index| self.regions[index].iter().copied())
+            .collect()
+    }
+
+    /// Mean position of the closest region that still counts, by manhattan distance
+    pub fn nearest_target_centre(&self, from: Coordinate) -> Option<(f32, f32)> {
+        self.target_regions()
+            .into_iter()
+            .map(|index| region_centre(&self.regions[index]))
+            .min_by(|a, b| {
+                let distance = |centre: &(f32, f32)| {
+                    (centre.0 - from.0 as f32).abs() + (centre.1 - from.1 as f32).abs()
+                };
+                distance(a).total_cmp(&distance(b))
+            })
+    }
+}
+
+pub fn region_centre(region: &[Coordinate]) -> (f32, f32) {
+    let count = region.len().max(1) as f32;
+    let (x_sum, y_sum) = region
+        .iter()
+        .fold((0, 0), |(x_sum, y_sum), (x, y)| (x_sum + x, y_sum + y));
+    (x_sum as f32 / count, y_sum as f32 / count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions() -> Vec<Vec<Coordinate>> {
+        vec![vec![(0, 0), (1, 0)], vec![(4, 4)]]
+    }
+
+    #[test]
+    fn test_modes_complete_on_the_right_paths() {
+        let backwards = [(4, 4), (2, 2), (1, 0)];
+
+        let any = GoalProgress::from_path(GoalMode::Any, regions(), &[(2, 2), (4, 4)]);
+        assert!(any.is_complete());
+
+        let all = GoalProgress::from_path(GoalMode::All, regions(), &backwards);
+        assert!(all.is_complete());
+
+        let ordered = GoalProgress::from_path(GoalMode::Ordered, regions(), &backwards);
+        assert_eq!(ordered.reached_count(), 1);
+        assert!(!ordered.is_complete());
+        assert_eq!(ordered.target_cells(), HashSet::from([(4, 4)]));
+    }
+
+    #[test]
+    fn test_nearest_target_centre_skips_reached_regions() {
+        let mut progress = GoalProgress::new(GoalMode::All, regions());
+        assert_eq!(progress.nearest_target_centre((3, 3)), Some((4.0, 4.0)));
+        assert!(progress.visit((4, 4)));
+        assert!(!progress.visit((4, 4)));
+        assert_eq!(progress.nearest_target_centre((3, 3)), Some((0.5, 0.0)));
+    }
+}
@@ -2,11 +2,15 @@ pub mod constants;
 pub mod direction;
 pub mod environment;
 pub mod environment_config;
+pub mod goals;
 pub mod maze;
 pub mod maze_gen;
+pub mod maze_stats;
 pub mod exploring_algorithms;
 pub mod solving_algorithms;
 pub mod render_system;
+pub mod rng_stream;
+pub mod topology;
 mod map_vec_conversion;
 
 #[cfg(feature = "python")]
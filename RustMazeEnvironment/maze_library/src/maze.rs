@@ -1,12 +1,15 @@
 pub mod maze {
-    use rand::Rng;
-    use serde::{Deserialize, Serialize};
+    use rand::{rngs::StdRng, Rng};
+    use serde::{de::Error, Deserialize, Deserializer, Serialize};
     use std::{
-        collections::{HashMap, HashSet},
+        collections::{HashMap, HashSet, VecDeque},
         usize,
     };
 
-    use crate::{direction::Direction, environment::environment::Coordinate};
+    use crate::{
+        constants::constants::NUMBER_OF_INPUT_FEATURES, direction::Direction,
+        environment::environment::Coordinate, topology::Topology,
+    };
 
     #[derive(Debug, Clone, PartialEq)]
     pub enum MoveError {
@@ -14,73 +17,649 @@ pub mod maze {
         InvalidDirection,
     }
 
-    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    /// A problem found by `Maze::validate`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum MazeError {
+        GridSizeMismatch { expected: usize, found: usize },
+        AsymmetricWall { coordinate: Coordinate, direction: Direction },
+        OutOfBoundsOpening { coordinate: Coordinate, direction: Direction },
+        UnreachableCell(Coordinate),
+        StartOutOfBounds(Coordinate),
+        EndOutOfBounds(Coordinate),
+        EmptyMask,
+        /// wrapping needs three cells each way, and an even number of triangles so the
+        /// cells across a seam point opposite ways
+        UnwrappableSize { width: usize, height: usize },
+        /// levels need a square grid that does not wrap, with a height they split evenly
+        UnlayerableMaze { levels: usize, height: usize },
+    }
+
+    const ALL_DIRECTIONS: [Direction; 8] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+        Direction::NorthEast,
+        Direction::SouthWest,
+        Direction::Up,
+        Direction::Down,
+    ];
+    const LAYERED_SIDES: [Direction; 6] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+        Direction::Up,
+        Direction::Down,
+    ];
+
+    /// The walls around a cell packed into a bitmask, one bit per `Direction`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize)]
+    #[serde(into = "u8")]
+    pub struct Walls(u8);
+
+    impl Walls {
+        pub const NONE: Walls = Walls(0);
+        /// Every side of a square cell, `Maze::closed_walls` gives the sides of any cell
+        pub const ALL: Walls = Walls(0b1111);
+
+        fn bit(direction: &Direction) -> u8 {
+            1 << (*direction as usize)
+        }
+
+        pub fn bits(&self) -> u8 {
+            self.0
+        }
+
+        pub fn contains(&self, direction: &Direction) -> bool {
+            self.0 & Walls::bit(direction) != 0
+        }
+
+        pub fn insert(&mut self, direction: Direction) -> bool {
+            let added = !self.contains(&direction);
+            self.0 |= Walls::bit(&direction);
+            added
+        }
+
+        pub fn remove(&mut self, direction: &Direction) -> bool {
+            let removed = self.contains(direction);
+            self.0 &= !Walls::bit(direction);
+            removed
+        }
+
+        pub fn len(&self) -> usize {
+            self.0.count_ones() as usize
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+
+        pub fn iter(&self) -> impl Iterator<Item = Direction> + '_ {
+            ALL_DIRECTIONS.into_iter().filter(|dir| self.contains(dir))
+        }
+
+        pub fn retain(&mut self, mut keep: impl FnMut(&Direction) -> bool) {
+            for dir in ALL_DIRECTIONS {
+                if self.contains(&dir) && !keep(&dir) {
+                    self.remove(&dir);
+                }
+            }
+        }
+    }
+
+    impl From<Walls> for u8 {
+        fn from(walls: Walls) -> u8 {
+            walls.0
+        }
+    }
+
+    impl FromIterator<Direction> for Walls {
+        fn from_iter<I: IntoIterator<Item = Direction>>(iter: I) -> Self {
+            let mut walls = Walls::NONE;
+            for dir in iter {
+                walls.insert(dir);
+            }
+            walls
+        }
+    }
+
+    impl<const N: usize> From<[Direction; N]> for Walls {
+        fn from(directions: [Direction; N]) -> Self {
+            directions.into_iter().collect()
+        }
+    }
+
+    //older files stored the walls as a list of direction names
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum WallsFormat {
+        Bits(u8),
+        Directions(Vec<Direction>),
+    }
+
+    impl<'de> Deserialize<'de> for Walls {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            match WallsFormat::deserialize(deserializer)? {
+                WallsFormat::Bits(bits) => Ok(Walls(bits)),
+                WallsFormat::Directions(directions) => Ok(directions.into_iter().collect()),
+            }
+        }
+    }
+
+    /// A read only view of one cell, handed out by `Maze::get_cell` and `Maze::cells`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
     pub struct Cell {
         pub coordinate: Coordinate,
-        pub walls: HashSet<Direction>,
+        pub walls: Walls,
+        /// East or South for a passage tunnelling under the cell along that axis
+        #[serde(default)]
+        pub under: Option<Direction>,
+    }
+
+    /// A locked passage out of `coordinate`, opened by holding the key with the same id
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Door {
+        pub coordinate: Coordinate,
+        pub direction: Direction,
+        pub key: usize,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Key {
+        pub coordinate: Coordinate,
+        pub id: usize,
+    }
+
+    /// A passage tunnelling under the cell at `coordinate` between the neighbours either side of
+    /// it, `under` is East for a tunnel running East to West and South for one running North to
+    /// South. The cell's own passage crosses over the other way.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Crossing {
+        pub coordinate: Coordinate,
+        pub under: Direction,
+    }
+
+    /// Two cells joined so stepping into either one carries on from the other
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Portal {
+        pub a: Coordinate,
+        pub b: Coordinate,
+    }
+
+    //the nested grid of cells written before the walls were packed
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum GridFormat {
+        Flat(Vec<Walls>),
+        Nested(Vec<Vec<Cell>>),
+    }
+
+    fn deserialize_grid<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Walls>, D::Error> {
+        match GridFormat::deserialize(deserializer)? {
+            GridFormat::Flat(grid) => Ok(grid),
+            GridFormat::Nested(columns) => {
+                let width = columns.len();
+                let height = columns.first().map_or(0, |column| column.len());
+                let mut grid = vec![Walls::ALL; width * height];
+                for cell in columns.into_iter().flatten() {
+                    let (x, y) = cell.coordinate;
+                    if x >= width || y >= height {
+                        return Err(D::Error::custom(format!(
+                            "cell {:?} is outside the {}x{} grid",
+                            cell.coordinate, width, height
+                        )));
+                    }
+                    grid[y * width + x] = cell.walls;
+                }
+                Ok(grid)
+            }
+        }
     }
 
     #[derive(Debug, Serialize, Deserialize, Clone)]
     pub struct Maze {
         pub width: usize,
         pub height: usize,
-        pub grid: Vec<Vec<Cell>>,
+        /// Row major walls, the cell at (x, y) is at `y * width + x`
+        #[serde(deserialize_with = "deserialize_grid")]
+        pub grid: Vec<Walls>,
         pub start: Coordinate,
+        /// Every goal cell, the union of the goal regions
         pub end: HashSet<Coordinate>,
+        //goal regions in task order, empty in files written before there could be several
+        #[serde(default)]
+        goals: Vec<Vec<Coordinate>>,
+        #[serde(default)]
+        pub doors: Vec<Door>,
+        #[serde(default)]
+        pub keys: Vec<Key>,
+        #[serde(default)]
+        pub portals: Vec<Portal>,
+        #[serde(default)]
+        pub crossings: Vec<Crossing>,
+        //cost of stepping into each cell in grid order, empty while every step costs 1
+        #[serde(default)]
+        terrain: Vec<u8>,
+        //cells that are part of the maze in grid order, empty while the whole rectangle is
+        #[serde(default)]
+        mask: Vec<bool>,
+        #[serde(default)]
+        pub topology: Topology,
+        //whether leaving one edge arrives on the opposite one
+        #[serde(default)]
+        wrap: bool,
+        //floors stacked down the rows, level n is the nth band of height / levels rows
+        #[serde(default = "one_level")]
+        levels: usize,
+    }
+
+    fn one_level() -> usize {
+        1
     }
 
     impl Maze {
         pub fn new(width: usize, height: usize) -> Self {
+            Maze::new_with_topology(width, height, Topology::Square)
+        }
+
+        /// A fully walled grid whose cells have the sides of `topology`
+        pub fn new_with_topology(width: usize, height: usize, topology: Topology) -> Self {
             Maze {
                 width,
                 height,
                 start: (0, height - 1),
                 end: HashSet::new(),
-                grid: (0..width)
-                    .map(|x| {
-                        (0..height)
-                            .map(move |y| Cell {
-                                coordinate: (x, y),
-                                walls: HashSet::from([
-                                    Direction::North,
-                                    Direction::South,
-                                    Direction::East,
-                                    Direction::West,
-                                ]),
-                            })
-                            .collect::<Vec<Cell>>()
+                goals: Vec::new(),
+                doors: Vec::new(),
+                keys: Vec::new(),
+                portals: Vec::new(),
+                crossings: Vec::new(),
+                terrain: Vec::new(),
+                mask: Vec::new(),
+                topology,
+                wrap: false,
+                levels: 1,
+                grid: (0..width * height)
+                    .map(|index| {
+                        topology
+                            .sides((index % width, index / width))
+                            .iter()
+                            .copied()
+                            .collect()
                     })
-                    .collect::<Vec<Vec<Cell>>>(),
+                    .collect(),
+            }
+        }
+
+        pub fn new_open(width: usize, height: usize) -> Self {
+            Maze::new_open_with_topology(width, height, Topology::Square)
+        }
+
+        pub fn new_open_with_topology(width: usize, height: usize, topology: Topology) -> Self {
+            let mut maze = Maze::new_with_topology(width, height, topology);
+            for index in 0..maze.number_of_cells() {
+                let coord = maze.coordinate_at(index);
+                //only keep the walls on the outer boundary
+                let boundary: Walls = maze
+                    .sides(coord)
+                    .iter()
+                    .copied()
+                    .filter(|dir| maze.move_from(dir, &coord, 1).is_err())
+                    .collect();
+                *maze.walls_mut(coord) = boundary;
             }
+            maze
         }
 
         pub fn init_maze(width: usize, height: usize) -> Maze {
-            let mut maze: Maze = Maze::new(width, height);
+            Maze::init_maze_with_topology(width, height, Topology::Square)
+        }
+
+        pub fn init_maze_with_topology(width: usize, height: usize, topology: Topology) -> Maze {
+            let mut maze: Maze = Maze::new_with_topology(width, height, topology);
             maze.set_end((width / 2, height / 2));
-            maze.set_starting_point((0, height - 1), None);
+            maze.set_starting_point((0, height - 1), None).unwrap();
             maze
         }
 
         pub fn set_end(&mut self, cell: Coordinate) {
-            self.end = HashSet::from([cell]);
+            self.set_goals(vec![vec![cell]]);
+        }
+
+        /// Replaces every goal with these regions, kept in the order given for ordered tasks
+        pub fn set_goals(&mut self, regions: Vec<Vec<Coordinate>>) {
+            self.goals = regions.into_iter().filter(|region| !region.is_empty()).collect();
+            self.end = self.goals.iter().flatten().copied().collect();
+        }
+
+        pub fn add_goal_region(&mut self, region: Vec<Coordinate>) {
+            if region.is_empty() {
+                return;
+            }
+            self.end.extend(region.iter().copied());
+            self.goals.push(region);
+        }
+
+        /// The goal regions in task order, an end set without regions counts as one region
+        pub fn goal_regions(&self) -> Vec<Vec<Coordinate>> {
+            if !self.goals.is_empty() {
+                return self.goals.clone();
+            }
+            if self.end.is_empty() {
+                return vec![];
+            }
+            let mut region: Vec<Coordinate> = self.end.iter().copied().collect();
+            region.sort();
+            vec![region]
         }
 
         pub fn number_of_cells(&self) -> usize {
             self.width * self.height
         }
         pub fn set_2x2_end(&mut self, cell: Coordinate) {
-            let end_set = [
+            self.set_goals(vec![vec![
                 cell,
                 (cell.0 - 1, cell.1),
                 (cell.0, cell.1 - 1),
                 (cell.0 - 1, cell.1 - 1),
-            ]
-            .into_iter()
-            .collect();
-            self.end = end_set;
+            ]]);
         }
+        /// Whether the position lands on a cell that is not masked off, positions off a
+        /// wrapped maze come back round from the other side
         pub fn in_bounds(&self, cell: (i32, i32)) -> bool {
-            cell.0 < self.width as i32 && cell.1 < self.height as i32 && cell.0 > -1 && cell.1 > -1
+            self.cell_at(cell).is_some()
+        }
+
+        /// The enabled cell a position lands on, wrapping round the edges when the maze wraps
+        pub fn cell_at(&self, position: (i32, i32)) -> Option<Coordinate> {
+            let (x, y) = self.wrapped(position);
+            if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+                return None;
+            }
+            let coord = (x as usize, y as usize);
+            self.is_enabled(coord).then_some(coord)
+        }
+
+        /// The enabled cell `offset` away from `coord` on the same level, what an agent can see
+        pub fn cell_near(&self, coord: Coordinate, offset: (i32, i32)) -> Option<Coordinate> {
+            self.cell_at((coord.0 as i32 + offset.0, coord.1 as i32 + offset.1))
+                .filter(|cell| self.level_of(*cell) == self.level_of(coord))
+        }
+
+        /// Whether the coordinate is itself an enabled cell of the grid, without wrapping
+        pub fn is_cell(&self, coord: Coordinate) -> bool {
+            coord.0 < self.width && coord.1 < self.height && self.is_enabled(coord)
+        }
+
+        fn wrapped(&self, position: (i32, i32)) -> (i32, i32) {
+            if !self.wrap {
+                return position;
+            }
+            (
+                position.0.rem_euclid(self.width as i32),
+                position.1.rem_euclid(self.height as i32),
+            )
+        }
+
+        pub fn is_wrapped(&self) -> bool {
+            self.wrap
+        }
+
+        /// Joins each edge to the opposite one, or seals the seams again. Passages across the
+        /// seams are walled up when wrapping is turned off.
+        pub fn set_wrap(&mut self, wrap: bool) -> Result<(), MazeError> {
+            if wrap && self.levels > 1 {
+                return Err(MazeError::UnlayerableMaze {
+                    levels: self.levels,
+                    height: self.height,
+                });
+            }
+            let odd_triangles = self.topology == Topology::Triangle
+                && (self.width % 2 == 1 || self.height % 2 == 1);
+            if wrap && (self.width < 3 || self.height < 3 || odd_triangles) {
+                return Err(MazeError::UnwrappableSize {
+                    width: self.width,
+                    height: self.height,
+                });
+            }
+            self.wrap = wrap;
+            for index in 0..self.number_of_cells() {
+                let coord = self.coordinate_at(index);
+                for &direction in self.sides(coord) {
+                    if self.move_from(&direction, &coord, 1).is_err() {
+                        self.walls_mut(coord).insert(direction);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        pub fn levels(&self) -> usize {
+            self.levels
+        }
+
+        /// Rows in each level, the whole height of a maze with one level
+        pub fn level_height(&self) -> usize {
+            self.height / self.levels
+        }
+
+        pub fn level_of(&self, coord: Coordinate) -> usize {
+            coord.1 / self.level_height()
+        }
+
+        /// Splits the rows into this many levels joined by stairs. Passages between what were
+        /// neighbouring rows of different levels are walled up, and the stairs all start shut
+        /// whenever the number of levels changes.
+        pub fn set_levels(&mut self, levels: usize) -> Result<(), MazeError> {
+            let unlayerable = levels > 1 && (self.topology != Topology::Square || self.wrap);
+            if levels == 0 || !self.height.is_multiple_of(levels) || unlayerable {
+                return Err(MazeError::UnlayerableMaze {
+                    levels,
+                    height: self.height,
+                });
+            }
+            let relayered = levels != self.levels;
+            self.levels = levels;
+            for index in 0..self.number_of_cells() {
+                let coord = self.coordinate_at(index);
+                let sides = self.sides(coord);
+                self.walls_mut(coord).retain(|dir| sides.contains(dir));
+                for &direction in sides {
+                    if (relayered && direction.is_vertical())
+                        || self.move_from(&direction, &coord, 1).is_err()
+                    {
+                        self.walls_mut(coord).insert(direction);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Whether stepping out of `coord` through this side comes back round from the far edge
+        pub fn crosses_seam(&self, coord: Coordinate, direction: Direction) -> bool {
+            let (dx, dy) = direction.offset();
+            let position = (coord.0 as i32 + dx, coord.1 as i32 + dy);
+            self.wrap && self.wrapped(position) != position
+        }
+
+        /// The side of `from` that opens onto the neighbouring cell `to`, across a seam or under a
+        /// crossing if need be
+        pub fn direction_to(&self, from: Coordinate, to: Coordinate) -> Option<Direction> {
+            self.sides(from)
+                .iter()
+                .copied()
+                .find(|direction| self.neighbour(from, *direction) == Ok(to))
+        }
+
+        /// The sides of the cell at `coord`, clockwise with the stairs after them
+        pub fn sides(&self, coord: Coordinate) -> &'static [Direction] {
+            if self.levels > 1 {
+                return &LAYERED_SIDES;
+            }
+            self.topology.sides(coord)
+        }
+
+        /// Every direction any cell can have a side in, in the order observations list them
+        pub fn directions(&self) -> &'static [Direction] {
+            if self.levels > 1 {
+                return &LAYERED_SIDES;
+            }
+            self.topology.directions()
+        }
+
+        /// How many actions an agent picks between, layered mazes add up and down the stairs
+        pub fn action_count(&self) -> usize {
+            if self.levels > 1 {
+                return LAYERED_SIDES.len();
+            }
+            self.topology.action_count()
+        }
+
+        /// The side an action picks at `coord`, on a layered maze 4 is Up and 5 is Down
+        pub fn action_direction(&self, coord: Coordinate, action: usize) -> Direction {
+            if self.levels > 1 {
+                return LAYERED_SIDES[action % LAYERED_SIDES.len()];
+            }
+            self.topology.action_direction(coord, action)
+        }
+
+        /// Observation length before any door or terrain features, each direction past the
+        /// four square sides adds three path flags and a wall for every cell of the 5x5 window
+        /// and a layered maze adds the levels of the agent and the goal
+        pub fn input_features(&self) -> usize {
+            let level_features = if self.levels > 1 { 2 } else { 0 };
+            NUMBER_OF_INPUT_FEATURES + (self.directions().len() - 4) * (3 + 5 * 5) + level_features
+        }
+
+        /// The walls of the cell at `coord` when every side is shut
+        pub fn closed_walls(&self, coord: Coordinate) -> Walls {
+            self.sides(coord).iter().copied().collect()
+        }
+
+        /// The sides of the cell at `coord` without a wall
+        pub fn open_sides(&self, coord: Coordinate) -> impl Iterator<Item = Direction> + '_ {
+            let walls = self.walls(coord);
+            self.sides(coord)
+                .iter()
+                .copied()
+                .filter(move |dir| !walls.contains(dir))
+        }
+
+        /// A random side of the cell at `coord`, square cells on one level draw exactly as
+        /// `Direction::random`
+        pub fn random_side(&self, coord: Coordinate, rng: &mut StdRng) -> Direction {
+            match self.topology {
+                Topology::Square if self.levels == 1 => Direction::random(rng),
+                _ => {
+                    let sides = self.sides(coord);
+                    sides[rng.random_range(0..sides.len())]
+                }
+            }
+        }
+
+        pub fn is_enabled(&self, coord: Coordinate) -> bool {
+            self.mask.get(self.index(coord)).is_none_or(|enabled| *enabled)
+        }
+
+        pub fn is_masked(&self) -> bool {
+            !self.mask.is_empty()
+        }
+
+        pub fn mask(&self) -> &[bool] {
+            &self.mask
+        }
+
+        pub fn enabled_cells(&self) -> impl Iterator<Item = Coordinate> + '_ {
+            (0..self.number_of_cells())
+                .map(|index| self.coordinate_at(index))
+                .filter(|coord| self.is_enabled(*coord))
+        }
+
+        pub fn enabled_count(&self) -> usize {
+            if self.mask.is_empty() {
+                self.number_of_cells()
+            } else {
+                self.mask.iter().filter(|enabled| **enabled).count()
+            }
+        }
+
+        /// A uniformly random enabled cell, drawing x then y until one is not masked off
+        pub fn random_enabled_cell(&self, rng: &mut StdRng) -> Coordinate {
+            loop {
+                let cell = (
+                    rng.random_range(0..self.width),
+                    rng.random_range(0..self.height),
+                );
+                if self.is_enabled(cell) {
+                    return cell;
+                }
+            }
+        }
+
+        /// The enabled cell closest to `coord` by manhattan distance, ties going to the first in
+        /// grid order
+        pub fn nearest_enabled(&self, coord: Coordinate) -> Coordinate {
+            if self.is_enabled(coord) {
+                return coord;
+            }
+            self.enabled_cells()
+                .min_by_key(|cell| cell.0.abs_diff(coord.0) + cell.1.abs_diff(coord.1))
+                .unwrap_or(coord)
+        }
+
+        /// Masks off the cells marked false, in grid order, walling them in so they act like
+        /// the outside of the maze. The enabled cells have to be connected and an empty mask
+        /// enables every cell. A start or goal on a masked cell moves to the nearest enabled one.
+        pub fn set_mask(&mut self, enabled: Vec<bool>) -> Result<(), MazeError> {
+            if !enabled.is_empty() {
+                if enabled.len() != self.grid.len() {
+                    return Err(MazeError::GridSizeMismatch {
+                        expected: self.grid.len(),
+                        found: enabled.len(),
+                    });
+                }
+                let first = enabled
+                    .iter()
+                    .position(|enabled| *enabled)
+                    .ok_or(MazeError::EmptyMask)?;
+                //only which cells neighbour each other matters, so leave out every wall
+                let mut open = Maze::new_with_topology(self.width, self.height, self.topology);
+                open.grid = vec![Walls::NONE; open.grid.len()];
+                open.wrap = self.wrap;
+                open.levels = self.levels;
+                open.mask = enabled.clone();
+                let distances = open.distances_from(self.coordinate_at(first));
+                if let Some(index) = (0..enabled.len())
+                    .find(|index| enabled[*index] && distances[*index].is_none())
+                {
+                    return Err(MazeError::UnreachableCell(self.coordinate_at(index)));
+                }
+            }
+
+            self.mask = Vec::new();
+            for index in (0..enabled.len()).filter(|index| !enabled[*index]) {
+                let coord = self.coordinate_at(index);
+                for direction in ALL_DIRECTIONS {
+                    //the edge of the grid is already walled
+                    let _ = self.close_wall(coord, direction);
+                }
+            }
+            self.mask = enabled;
+
+            self.start = self.nearest_enabled(self.start);
+            let goals = self
+                .goal_regions()
+                .into_iter()
+                .map(|region| {
+                    region
+                        .into_iter()
+                        .map(|cell| self.nearest_enabled(cell))
+                        .collect()
+                })
+                .collect();
+            self.set_goals(goals);
+            Ok(())
         }
 
         pub fn get_starting_point(&self) -> Coordinate {
@@ -94,15 +673,234 @@ pub mod maze {
             &mut self,
             coordinates: Coordinate,
             delete_wall: Option<&Direction>,
-        ) {
-            let cell = &mut self.grid[coordinates.0][coordinates.1];
+        ) -> Result<(), MoveError> {
+            if !self.is_cell(coordinates) {
+                return Err(MoveError::OutOfBounds);
+            }
             if let Some(wall) = delete_wall {
-                cell.walls.remove(wall);
+                self.open_wall(coordinates, *wall)?;
             }
+            self.start = coordinates;
+            Ok(())
         }
 
-        pub fn get_cell(&self, coord: Coordinate) -> &Cell {
-            &self.grid[coord.0][coord.1]
+        /// The door on the passage leaving `coord` in `direction`, from either side
+        pub fn door_between(&self, coord: Coordinate, direction: Direction) -> Option<&Door> {
+            let neighbour = self.neighbour(coord, direction).ok()?;
+            self.doors.iter().find(|door| {
+                (door.coordinate == coord && door.direction == direction)
+                    || (door.coordinate == neighbour
+                        && door.direction == direction.opposite_direction())
+            })
+        }
+
+        pub fn key_at(&self, coord: Coordinate) -> Option<usize> {
+            self.keys
+                .iter()
+                .find(|key| key.coordinate == coord)
+                .map(|key| key.id)
+        }
+
+        pub fn portal_partner(&self, coord: Coordinate) -> Option<Coordinate> {
+            self.portals.iter().find_map(|portal| {
+                if portal.a == coord {
+                    Some(portal.b)
+                } else if portal.b == coord {
+                    Some(portal.a)
+                } else {
+                    None
+                }
+            })
+        }
+
+        /// Where a weighted graph edge ends up, carried through a portal at the landing cell
+        pub fn destination(
+            &self,
+            direction: &Direction,
+            coordinates: &Coordinate,
+            steps: usize,
+        ) -> Result<Coordinate, MoveError> {
+            let landing = self.move_from_with_walls(direction, coordinates, steps)?;
+            Ok(self.portal_partner(landing).unwrap_or(landing))
+        }
+
+        /// What stepping into `coord` costs, 1 until terrain has been laid
+        pub fn cost(&self, coord: Coordinate) -> usize {
+            self.terrain
+                .get(self.index(coord))
+                .map_or(1, |cost| *cost as usize)
+        }
+
+        /// Sets the cost of stepping into one cell, a cost of 0 is raised to 1
+        pub fn set_cost(&mut self, coord: Coordinate, cost: u8) {
+            if self.terrain.is_empty() {
+                self.terrain = vec![1; self.grid.len()];
+            }
+            let index = self.index(coord);
+            self.terrain[index] = cost.max(1);
+        }
+
+        /// Replaces the terrain with costs in grid order, an empty layer makes every step cost 1
+        pub fn set_terrain(&mut self, costs: Vec<u8>) -> Result<(), MazeError> {
+            if !costs.is_empty() && costs.len() != self.grid.len() {
+                return Err(MazeError::GridSizeMismatch {
+                    expected: self.grid.len(),
+                    found: costs.len(),
+                });
+            }
+            self.terrain = costs.into_iter().map(|cost| cost.max(1)).collect();
+            Ok(())
+        }
+
+        pub fn terrain(&self) -> &[u8] {
+            &self.terrain
+        }
+
+        pub fn max_cost(&self) -> usize {
+            self.terrain.iter().max().map_or(1, |cost| *cost as usize)
+        }
+
+        /// The axis of the tunnel under `coord`, East or South, if there is one
+        pub fn crossing_at(&self, coord: Coordinate) -> Option<Direction> {
+            self.crossings
+                .iter()
+                .find(|crossing| crossing.coordinate == coord)
+                .map(|crossing| crossing.under)
+        }
+
+        /// Whether heading this way into `coord` goes under it rather than into it
+        pub fn tunnels_under(&self, coord: Coordinate, direction: Direction) -> bool {
+            self.crossing_at(coord)
+                .is_some_and(|under| under == direction || under == direction.opposite_direction())
+        }
+
+        /// The cell a passage out of `coord` this way leads to, carrying on to the far side of
+        /// a crossing it tunnels under
+        pub fn neighbour(&self, coord: Coordinate, direction: Direction) -> Result<Coordinate, MoveError> {
+            let next = self.move_from(&direction, &coord, 1)?;
+            if self.tunnels_under(next, direction) {
+                return self.move_from(&direction, &next, 1);
+            }
+            Ok(next)
+        }
+
+        /// Cost of walking `steps` cells from `coordinates`, each cell entered is charged
+        pub fn edge_cost(&self, direction: &Direction, coordinates: &Coordinate, steps: usize) -> usize {
+            if self.terrain.is_empty() {
+                return steps;
+            }
+            (1..=steps)
+                .map(|step| {
+                    self.move_from_with_walls(direction, coordinates, step)
+                        .map_or(1, |cell| self.cost(cell))
+                })
+                .sum()
+        }
+
+        /// Removes the wall on both sides, returns the neighbour it now opens onto
+        pub fn open_wall(
+            &mut self,
+            coord: Coordinate,
+            direction: Direction,
+        ) -> Result<Coordinate, MoveError> {
+            let neighbour = self.neighbour(coord, direction)?;
+            self.walls_mut(coord).remove(&direction);
+            self.walls_mut(neighbour).remove(&direction.opposite_direction());
+            Ok(neighbour)
+        }
+
+        /// Adds the wall on both sides, returns the neighbour it now separates
+        pub fn close_wall(
+            &mut self,
+            coord: Coordinate,
+            direction: Direction,
+        ) -> Result<Coordinate, MoveError> {
+            let neighbour = self.neighbour(coord, direction)?;
+            self.walls_mut(coord).insert(direction);
+            self.walls_mut(neighbour).insert(direction.opposite_direction());
+            Ok(neighbour)
+        }
+
+        /// Flips the wall on both sides, returns true if it is open afterwards
+        pub fn toggle_wall(
+            &mut self,
+            coord: Coordinate,
+            direction: Direction,
+        ) -> Result<bool, MoveError> {
+            if self.walls(coord).contains(&direction) {
+                self.open_wall(coord, direction)?;
+                Ok(true)
+            } else {
+                self.close_wall(coord, direction)?;
+                Ok(false)
+            }
+        }
+
+        fn index(&self, coord: Coordinate) -> usize {
+            coord.1 * self.width + coord.0
+        }
+
+        pub fn get_cell(&self, coord: Coordinate) -> Cell {
+            Cell {
+                coordinate: coord,
+                walls: self.walls(coord),
+                under: self.crossing_at(coord),
+            }
+        }
+
+        pub fn walls(&self, coord: Coordinate) -> Walls {
+            self.grid[self.index(coord)]
+        }
+
+        /// Edits one side of the walls only, use `open_wall` or `close_wall` to keep neighbours in step
+        pub fn walls_mut(&mut self, coord: Coordinate) -> &mut Walls {
+            let index = self.index(coord);
+            &mut self.grid[index]
+        }
+
+        /// Every cell in row major order
+        pub fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+            self.grid.iter().enumerate().map(|(index, walls)| Cell {
+                coordinate: self.coordinate_at(index),
+                walls: *walls,
+                under: self.crossing_at(self.coordinate_at(index)),
+            })
+        }
+
+        /// The coordinate of a row major grid index
+        pub fn coordinate_at(&self, index: usize) -> Coordinate {
+            (index % self.width, index / self.width)
+        }
+
+        /// Steps from `from` to every cell through open walls, tunnels and portals, indexed like
+        /// `grid`
+        pub fn distances_from(&self, from: Coordinate) -> Vec<Option<usize>> {
+            let mut distances = vec![None; self.grid.len()];
+            let mut queue = VecDeque::from([from]);
+            distances[self.index(from)] = Some(0);
+            while let Some(current) = queue.pop_front() {
+                let distance = distances[self.index(current)].unwrap_or(0);
+                for direction in ALL_DIRECTIONS {
+                    if let Ok(next) = self.move_from_with_walls(&direction, &current, 1) {
+                        let index = self.index(next);
+                        if distances[index].is_some() {
+                            continue;
+                        }
+                        distances[index] = Some(distance + 1);
+                        //stepping into a portal carries on from its partner for free
+                        match self.portal_partner(next) {
+                            Some(partner) if distances[self.index(partner)].is_none() => {
+                                let partner_index = self.index(partner);
+                                distances[partner_index] = Some(distance + 1);
+                                queue.push_back(partner);
+                            }
+                            Some(_) => {}
+                            None => queue.push_back(next),
+                        }
+                    }
+                }
+            }
+            distances
         }
 
         pub fn move_from_with_walls(
@@ -113,14 +911,11 @@ pub mod maze {
         ) -> Result<Coordinate, MoveError> {
             let mut current = *coordinates;
             for _ in 0..steps {
-                if self.grid[current.0][current.1]
-                    .walls
-                    .contains(direction)
-                {
-                    
+                if self.walls(current).contains(direction) {
                     return Err(MoveError::InvalidDirection);
                 }
-                current = match self.move_from(direction, &current, 1) {
+                //a tunnel under a crossing counts as one step
+                current = match self.neighbour(current, *direction) {
                     Ok(c) => c,
                     Err(_) => return Err(MoveError::OutOfBounds),
                 };
@@ -136,68 +931,196 @@ pub mod maze {
         ) -> Result<Coordinate, MoveError> {
             let i32steps = steps as i32;
             let (x, y) = (coordinates.0 as i32, coordinates.1 as i32);
-            let new_coordinates = match direction {
-                Direction::North => (x, y - i32steps),
-                Direction::South => (x, y + i32steps),
-                Direction::East => (x + i32steps, y),
-                Direction::West => (x - i32steps, y),
+            if direction.is_vertical() {
+                let climb = if *direction == Direction::Up { i32steps } else { -i32steps };
+                let level = self.level_of(*coordinates) as i32 + climb;
+                if self.levels == 1 || level < 0 || level >= self.levels as i32 {
+                    return Err(MoveError::OutOfBounds);
+                }
+                return self
+                    .cell_at((x, y + climb * self.level_height() as i32))
+                    .ok_or(MoveError::OutOfBounds);
+            }
+            let (dx, dy) = direction.offset();
+            //a cell without a side that way has nothing beyond it, triangles can only keep
+            //going North or South for a single step
+            let has_sides = match self.topology {
+                Topology::Square => !direction.is_diagonal(),
+                _ => (0..steps.max(1) as i32).all(|step| {
+                    let (sx, sy) = self.wrapped((x + dx * step, y + dy * step));
+                    sx >= 0 && sy >= 0 && self.topology.has_side((sx as usize, sy as usize), *direction)
+                }),
             };
-            if !self.in_bounds(new_coordinates) {
+            if !has_sides {
                 return Err(MoveError::OutOfBounds);
             }
-            Ok((new_coordinates.0 as usize, new_coordinates.1 as usize))
+            //rows of different levels are not neighbours, only the stairs join them
+            self.cell_at((x + dx * i32steps, y + dy * i32steps))
+                .filter(|cell| self.level_of(*cell) == self.level_of(*coordinates))
+                .ok_or(MoveError::OutOfBounds)
         }
 
         pub fn break_walls_for_path(&mut self, path: Vec<(Coordinate, Direction)>) {
-            for index in 0..path.len() {
-                let current_cell = path[index].0;
-                let next_cell = match self.move_from(&path[index].1, &path[index].0, 1) {
-                    Ok(coordinates) => coordinates,
-                    Err(_) => {
-                        return;
-                    }
-                };
-                let direction = path[index].1;
-                self.grid[next_cell.0][next_cell.1]
-                    .walls
-                    .remove(&Direction::opposite_direction(&direction));
-                self.grid[current_cell.0][current_cell.1]
-                    .walls
-                    .remove(&direction);
+            for (current_cell, direction) in path {
+                if self.open_wall(current_cell, direction).is_err() {
+                    return;
+                }
             }
         }
 
-        pub fn get_perfect_end_centre(&self) -> (f32, f32) {
-            (
-                self.width as f32 / 2.0 - 0.5,
-                self.height as f32 / 2.0 - 0.5,
-            )
+        pub fn add_walls_for_path(&mut self, path: Vec<(Coordinate, Direction)>) {
+            for (current_cell, direction) in path {
+                if self.close_wall(current_cell, direction).is_err() {
+                    return;
+                }
+            }
+        }
+
+        /// The mean position of the end cells, the middle of the grid when there are none
+        pub fn goal_centre(&self) -> (f32, f32) {
+            if self.end.is_empty() {
+                return (
+                    self.width as f32 / 2.0 - 0.5,
+                    self.height as f32 / 2.0 - 0.5,
+                );
+            }
+            let count = self.end.len() as f32;
+            let (x_sum, y_sum) = self
+                .end
+                .iter()
+                .fold((0, 0), |(x_sum, y_sum), (x, y)| (x_sum + x, y_sum + y));
+            (x_sum as f32 / count, y_sum as f32 / count)
         }
 
         pub fn break_end_walls(&mut self) {
-            for &(x, y) in self.end.iter() {
+            for (x, y) in self.end.clone() {
                 let current = (x, y);
 
-                // Check each cardinal direction
-                for dir in [
-                    Direction::North,
-                    Direction::East,
-                    Direction::South,
-                    Direction::West,
-                ] {
-                    if let Ok(neighbor) = self.move_from(&dir, &current, 1) {
+                // Check each side of the cell
+                for dir in self.sides(current) {
+                    if let Ok(neighbor) = self.move_from(dir, &current, 1) {
                         if self.end.contains(&neighbor) {
                             // Remove the wall between current and neighbor
-                            self.grid[current.0][current.1].walls.remove(&dir);
-                            self.grid[neighbor.0][neighbor.1]
-                                .walls
-                                .remove(&Direction::opposite_direction(&dir));
+                            let _ = self.open_wall(current, *dir);
                         }
                     }
                 }
             }
         }
 
+        /// Checks the walls agree on both sides, the boundary is closed, every cell can be
+        /// reached from the start and the start and end cells are on the grid
+        pub fn validate(&self) -> Result<(), Vec<MazeError>> {
+            let expected = self.width * self.height;
+            if self.grid.len() != expected {
+                return Err(vec![MazeError::GridSizeMismatch {
+                    expected,
+                    found: self.grid.len(),
+                }]);
+            }
+
+            let mut errors = Vec::new();
+            if !self.terrain.is_empty() && self.terrain.len() != expected {
+                errors.push(MazeError::GridSizeMismatch {
+                    expected,
+                    found: self.terrain.len(),
+                });
+            }
+            for cell in self.cells() {
+                for &direction in self.sides(cell.coordinate) {
+                    match self.neighbour(cell.coordinate, direction) {
+                        //a tunnel goes past the sides of the cell it runs under, which stay shut
+                        _ if self.tunnels_under(cell.coordinate, direction)
+                            && !cell.walls.contains(&direction) =>
+                        {
+                            errors.push(MazeError::AsymmetricWall {
+                                coordinate: cell.coordinate,
+                                direction,
+                            });
+                        }
+                        _ if self.tunnels_under(cell.coordinate, direction) => {}
+                        Err(_) if !cell.walls.contains(&direction) => {
+                            errors.push(MazeError::OutOfBoundsOpening {
+                                coordinate: cell.coordinate,
+                                direction,
+                            });
+                        }
+                        //only check one side of each pair so each shared wall is reported once
+                        Ok(neighbour)
+                            if matches!(
+                                direction,
+                                Direction::East
+                                    | Direction::South
+                                    | Direction::SouthWest
+                                    | Direction::Up
+                            )
+                                && self.one_sided_wall(cell.coordinate, neighbour, direction) =>
+                        {
+                            errors.push(MazeError::AsymmetricWall {
+                                coordinate: cell.coordinate,
+                                direction,
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            let start_in_bounds = self.is_cell(self.start);
+            if !start_in_bounds {
+                errors.push(MazeError::StartOutOfBounds(self.start));
+            }
+            let mut ends: Vec<&Coordinate> = self.end.iter().collect();
+            ends.sort();
+            for end in ends {
+                if !self.is_cell(*end) {
+                    errors.push(MazeError::EndOutOfBounds(*end));
+                }
+            }
+
+            if start_in_bounds {
+                let distances = self.distances_from(self.start);
+                errors.extend(
+                    distances
+                        .iter()
+                        .enumerate()
+                        .filter(|(index, distance)| {
+                            distance.is_none() && self.is_enabled(self.coordinate_at(*index))
+                        })
+                        .map(|(index, _)| MazeError::UnreachableCell(self.coordinate_at(index))),
+                );
+            }
+
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(errors)
+            }
+        }
+
+        /// Cells holding a key, portal or either side of a door
+        pub fn special_cells(&self) -> Vec<Coordinate> {
+            let mut cells: Vec<Coordinate> = self.keys.iter().map(|key| key.coordinate).collect();
+            cells.extend(self.portals.iter().flat_map(|portal| [portal.a, portal.b]));
+            for door in &self.doors {
+                cells.push(door.coordinate);
+                if let Ok(neighbour) = self.neighbour(door.coordinate, door.direction) {
+                    cells.push(neighbour);
+                }
+            }
+            cells
+        }
+
+        fn one_sided_wall(
+            &self,
+            coord: Coordinate,
+            neighbour: Coordinate,
+            direction: Direction,
+        ) -> bool {
+            self.walls(coord).contains(&direction)
+                != self.walls(neighbour).contains(&direction.opposite_direction())
+        }
+
         fn follow_path(
             &self,
             coordinates: &Coordinate,
@@ -219,30 +1142,32 @@ pub mod maze {
             steps
         }
 
-        pub fn break_random_walls(&mut self, amount: usize) -> Vec<(Coordinate, Direction)> {
+        pub fn break_random_walls(
+            &mut self,
+            amount: usize,
+            rng: &mut StdRng,
+        ) -> Vec<(Coordinate, Direction)> {
             let mut edge_set: Vec<(Coordinate, Direction)> = Vec::new();
             let mut walls_to_break: Vec<(Coordinate, Direction)> = Vec::new();
             for x in 0..self.width {
                 for y in 0..self.height {
-                    let valid_directions = match (x + 1 < self.width, y + 1 < self.height) {
-                        (true, true) => &vec![Direction::South, Direction::East], // Both directions
-                        (false, true) => &vec![Direction::South],                 // Only South
-                        (true, false) => &vec![Direction::East],                  // Only East
-                        _ => &vec![],                                             // No valid moves
-                    };
-
+                    //walk the fixed direction list, set order would change between runs
+                    if !self.is_enabled((x, y)) {
+                        continue;
+                    }
                     edge_set.extend(
-                        self.grid[x][y]
-                            .walls
+                        [Direction::South, Direction::East, Direction::SouthWest, Direction::Up]
                             .iter()
-                            .filter(|dir| valid_directions.contains(dir))
+                            .filter(|dir| self.walls((x, y)).contains(dir))
+                            .filter(|dir| !self.tunnels_under((x, y), **dir))
+                            .filter(|dir| self.move_from(dir, &(x, y), 1).is_ok())
                             .map(|dir| ((x, y), *dir)),
                     );
                 }
             }
 
             while walls_to_break.len() < amount {
-                let set_to_remove = edge_set.remove(rand::rng().random_range(0..edge_set.len()));
+                let set_to_remove = edge_set.remove(rng.random_range(0..edge_set.len()));
                 let moved_coordinates = &self
                     .move_from(&set_to_remove.1, &set_to_remove.0, 1)
                     .unwrap();
@@ -275,40 +1200,39 @@ pub mod maze {
             }
             decision_set.insert(self.start);
             decision_set.extend(self.end.clone());
+            //stop corridors at doors, keys and portals so an agent can be turned back, pick
+            //one up or be carried off
+            for coordinate in self.special_cells() {
+                decision_nodes.insert(coordinate, HashMap::new());
+                decision_set.insert(coordinate);
+            }
 
-            for row in 0..self.height {
-                for column in 0..self.width {
-                    if visited.is_some() && !visited_to_use.contains_key(&(row, column)) {
-                        continue;
-                    }
-                    let cell = &self.grid[row][column];
-                    if !skip_non_decision_nodes {
-                        decision_nodes
-                            .insert((cell.coordinate.0, cell.coordinate.1), HashMap::new());
-                        decision_set.insert((cell.coordinate.0, cell.coordinate.1));
-                        continue;
-                    }
-                    let walls: Vec<&Direction> = cell.walls.iter().collect();
-                    if walls.len() <= 1 || walls.len() == 3 {
-                        decision_nodes
-                            .insert((cell.coordinate.0, cell.coordinate.1), HashMap::new());
-                        decision_set.insert((cell.coordinate.0, cell.coordinate.1));
-                    }
-                    if walls.len() == 2 && *walls[0] != walls[1].opposite_direction() {
-                        decision_nodes
-                            .insert((cell.coordinate.0, cell.coordinate.1), HashMap::new());
-                        decision_set.insert((cell.coordinate.0, cell.coordinate.1));
-                    }
+            for cell in self.cells() {
+                if visited.is_some() && !visited_to_use.contains_key(&cell.coordinate) {
+                    continue;
+                }
+                if !self.is_enabled(cell.coordinate) {
+                    continue;
+                }
+                if !skip_non_decision_nodes {
+                    decision_nodes.insert(cell.coordinate, HashMap::new());
+                    decision_set.insert(cell.coordinate);
+                    continue;
+                }
+                //two opposite openings is a straight corridor, a closed cell is never reached
+                let mut open = self.open_sides(cell.coordinate);
+                let decision_point = match (open.next(), open.next(), open.next()) {
+                    (None, _, _) => false,
+                    (Some(first), Some(second), None) => first != second.opposite_direction(),
+                    _ => true,
+                };
+                if decision_point {
+                    decision_nodes.insert(cell.coordinate, HashMap::new());
+                    decision_set.insert(cell.coordinate);
                 }
             }
-            let directions = vec![
-                Direction::North,
-                Direction::South,
-                Direction::East,
-                Direction::West,
-            ];
             for (coordinate, inner_map) in decision_nodes.iter_mut() {
-                for direction in &directions {
+                for direction in self.sides(*coordinate) {
                     let steps = self.follow_path(&coordinate, &direction, &decision_set);
                     if steps > 0 {
                         inner_map.insert(*direction, steps);
@@ -321,10 +1245,14 @@ pub mod maze {
     }
     #[cfg(test)]
     mod tests {
+        use strum::IntoEnumIterator;
+
         use super::*;
-        use crate::direction::Direction;
-        
-        use std::collections::HashSet;
+        use crate::{
+            direction::Direction,
+            maze_gen::maze_gen_handler::MazeType,
+            test_utils::generator_output::{check_raw_passages, raw_passages},
+        };
 
         #[test]
         fn test_move_from_valid_directions() {
@@ -363,8 +1291,7 @@ pub mod maze {
         fn test_move_from_with_walls_blocked() {
             let mut maze = Maze::new(3, 3);
             let coord = (1, 1);
-            let cell = &mut maze.grid[coord.0][coord.1];
-            cell.walls = HashSet::from([
+            *maze.walls_mut(coord) = Walls::from([
                 Direction::North,
                 Direction::South,
                 Direction::East,
@@ -384,19 +1311,231 @@ pub mod maze {
             }
         }
 
+        #[test]
+        fn test_new_open_only_has_boundary_walls() {
+            let mut maze = Maze::new_open(4, 4);
+            assert_eq!(maze.move_from_with_walls(&Direction::East, &(0, 1), 3), Ok((3, 1)));
+            assert_eq!(
+                maze.move_from_with_walls(&Direction::West, &(0, 1), 1),
+                Err(MoveError::InvalidDirection)
+            );
+
+            maze.add_walls_for_path(vec![((1, 1), Direction::East)]);
+            assert!(maze.get_cell((2, 1)).walls.contains(&Direction::West));
+            assert_eq!(
+                maze.move_from_with_walls(&Direction::East, &(0, 1), 3),
+                Err(MoveError::InvalidDirection)
+            );
+        }
+
+        #[test]
+        fn test_walls_round_trip_through_json() {
+            let mut maze = Maze::new(3, 2);
+            maze.break_walls_for_path(vec![((0, 0), Direction::East), ((1, 0), Direction::South)]);
+
+            let json = serde_json::to_string(&maze).unwrap();
+            let reloaded: Maze = serde_json::from_str(&json).unwrap();
+            assert_eq!(reloaded.grid, maze.grid);
+            assert_eq!(reloaded.walls((1, 0)), Walls::from([Direction::North, Direction::East]));
+        }
+
+        #[test]
+        fn test_reads_legacy_nested_grid_json() {
+            let json = r#"{
+                "width": 2,
+                "height": 1,
+                "grid": [
+                    [{"coordinate": [0, 0], "walls": ["North", "South", "West"]}],
+                    [{"coordinate": [1, 0], "walls": ["North", "South", "East"]}]
+                ],
+                "start": [0, 0],
+                "end": [[1, 0]]
+            }"#;
+            let maze: Maze = serde_json::from_str(json).unwrap();
+            assert_eq!(maze.move_from_with_walls(&Direction::East, &(0, 0), 1), Ok((1, 0)));
+            assert_eq!(
+                maze.get_cell((1, 0)).walls,
+                Walls::from([Direction::North, Direction::South, Direction::East])
+            );
+        }
+
         #[test]
         fn test_move_from_with_walls_unblocked() {
             let mut maze = Maze::new(4, 4);
             let coord = (1, 1);
 
             // Remove east wall to allow movement eastward
-            maze.grid[1][1].walls.remove(&Direction::East);
-            maze.grid[2][1].walls.remove(&Direction::West); // Also remove opposite wall
-            maze.grid[2][1].walls.remove(&Direction::East);
-            maze.grid[3][1].walls.remove(&Direction::West); // Also remove opposite wall
+            maze.open_wall((1, 1), Direction::East).unwrap();
+            maze.open_wall((2, 1), Direction::East).unwrap();
 
             let result = maze.move_from_with_walls(&Direction::East, &coord, 2);
             assert_eq!(result, Ok((3, 1)));
         }
+
+        #[test]
+        fn test_wall_edits_keep_both_sides_in_step() {
+            let mut maze = Maze::new(3, 3);
+            assert_eq!(maze.open_wall((1, 1), Direction::North), Ok((1, 0)));
+            assert!(!maze.walls((1, 0)).contains(&Direction::South));
+            assert_eq!(maze.toggle_wall((1, 0), Direction::South), Ok(false));
+            assert!(maze.walls((1, 1)).contains(&Direction::North));
+            assert_eq!(maze.toggle_wall((1, 0), Direction::South), Ok(true));
+            assert_eq!(maze.close_wall((1, 1), Direction::North), Ok((1, 0)));
+            assert_eq!(maze.walls((1, 0)), Walls::ALL);
+            assert_eq!(maze.open_wall((0, 0), Direction::West), Err(MoveError::OutOfBounds));
+            assert_eq!(maze.walls((0, 0)), Walls::ALL);
+        }
+
+        #[test]
+        fn test_set_starting_point_moves_start() {
+            let mut maze = Maze::new(3, 3);
+            maze.set_starting_point((1, 1), Some(&Direction::East)).unwrap();
+            assert_eq!(maze.get_starting_point(), (1, 1));
+            assert!(!maze.walls((2, 1)).contains(&Direction::West));
+            assert_eq!(maze.set_starting_point((3, 0), None), Err(MoveError::OutOfBounds));
+            assert_eq!(maze.get_starting_point(), (1, 1));
+        }
+
+        #[test]
+        fn test_validate_reports_broken_walls() {
+            let mut maze = Maze::new(2, 2);
+            maze.set_end((1, 0));
+            maze.break_walls_for_path(vec![
+                ((0, 1), Direction::North),
+                ((0, 0), Direction::East),
+                ((1, 0), Direction::South),
+            ]);
+            assert_eq!(maze.validate(), Ok(()));
+
+            maze.walls_mut((1, 1)).remove(&Direction::West);
+            maze.walls_mut((0, 0)).remove(&Direction::North);
+            maze.end.insert((5, 5));
+            assert_eq!(
+                maze.validate(),
+                Err(vec![
+                    MazeError::OutOfBoundsOpening {
+                        coordinate: (0, 0),
+                        direction: Direction::North
+                    },
+                    MazeError::AsymmetricWall {
+                        coordinate: (0, 1),
+                        direction: Direction::East
+                    },
+                    MazeError::EndOutOfBounds((5, 5)),
+                ])
+            );
+
+            let mut closed = Maze::new(2, 1);
+            closed.start = (0, 0);
+            assert_eq!(closed.validate(), Err(vec![MazeError::UnreachableCell((1, 0))]));
+        }
+
+        #[test]
+        fn test_mask_walls_off_cells_and_moves_the_task() {
+            let mut maze = Maze::init_maze(3, 3);
+            maze.break_walls_for_path(vec![((0, 2), Direction::East)]);
+            assert_eq!(
+                maze.set_mask(vec![true, false, true, false, true, false, true, false, true]),
+                Err(MazeError::UnreachableCell((2, 0)))
+            );
+
+            maze.set_mask(vec![true, true, true, true, false, true, false, true, true])
+                .unwrap();
+            assert!(!maze.in_bounds((1, 1)));
+            assert_eq!(maze.move_from(&Direction::North, &(1, 2), 1), Err(MoveError::OutOfBounds));
+            assert_eq!(maze.walls((1, 2)), Walls::ALL);
+            assert_eq!(maze.start, (0, 1));
+            assert_eq!(maze.end, [(1, 0)].into());
+            assert_eq!(maze.enabled_count(), 7);
+        }
+
+        #[test]
+        fn test_wrapped_moves_come_back_round_the_far_edge() {
+            let mut maze = Maze::new(4, 3);
+            maze.set_wrap(true).unwrap();
+            assert_eq!(maze.move_from(&Direction::East, &(3, 1), 1), Ok((0, 1)));
+            assert_eq!(maze.move_from(&Direction::North, &(1, 0), 2), Ok((1, 1)));
+            assert_eq!(maze.cell_at((-1, 3)), Some((3, 0)));
+            assert!(!maze.is_cell((4, 0)));
+
+            maze.open_wall((3, 1), Direction::East).unwrap();
+            assert!(maze.crosses_seam((3, 1), Direction::East));
+            assert_eq!(maze.direction_to((0, 1), (3, 1)), Some(Direction::West));
+            assert!(!maze.walls((0, 1)).contains(&Direction::West));
+
+            maze.set_wrap(false).unwrap();
+            assert!(maze.walls((3, 1)).contains(&Direction::East));
+            assert_eq!(maze.move_from(&Direction::East, &(3, 1), 1), Err(MoveError::OutOfBounds));
+
+            let too_small = MazeError::UnwrappableSize { width: 2, height: 5 };
+            assert_eq!(Maze::new(2, 5).set_wrap(true), Err(too_small));
+            let mut triangles = Maze::new_with_topology(5, 4, Topology::Triangle);
+            assert!(triangles.set_wrap(true).is_err());
+        }
+
+        #[test]
+        fn test_levels_are_only_joined_by_stairs() {
+            let mut maze = Maze::new(3, 4);
+            maze.set_levels(2).unwrap();
+            assert_eq!(maze.level_of((1, 2)), 1);
+            assert_eq!(maze.move_from(&Direction::North, &(1, 2), 1), Err(MoveError::OutOfBounds));
+            assert_eq!(maze.move_from(&Direction::Up, &(1, 0), 1), Ok((1, 2)));
+            assert_eq!(maze.move_from(&Direction::Down, &(2, 3), 1), Ok((2, 1)));
+            assert_eq!(maze.move_from(&Direction::Up, &(2, 3), 1), Err(MoveError::OutOfBounds));
+            assert!(maze.walls((1, 2)).contains(&Direction::Down));
+            assert_eq!(maze.cell_near((1, 1), (0, 1)), None);
+
+            assert_eq!(maze.open_wall((1, 0), Direction::Up), Ok((1, 2)));
+            assert!(!maze.walls((1, 2)).contains(&Direction::Down));
+            assert_eq!(maze.action_direction((0, 0), 5), Direction::Down);
+
+            let uneven = MazeError::UnlayerableMaze { levels: 3, height: 4 };
+            assert_eq!(maze.set_levels(3), Err(uneven));
+            assert!(maze.set_wrap(true).is_err());
+            assert!(Maze::new_with_topology(4, 4, Topology::Hex).set_levels(2).is_err());
+        }
+
+        #[test]
+        fn test_generators_carve_between_levels() {
+            for maze_type in MazeType::iter() {
+                let mut maze = Maze::init_maze(6, 8);
+                maze.set_levels(2).unwrap();
+                let walls = raw_passages(&maze, &maze_type, 5);
+
+                assert_eq!(check_raw_passages(&maze, &walls), Ok(()), "{}", maze_type);
+                assert!(
+                    walls.iter().any(|(_, direction)| direction.is_vertical()),
+                    "{}",
+                    maze_type
+                );
+            }
+        }
+
+        #[test]
+        fn test_generators_carve_across_the_seams() {
+            for topology in [Topology::Square, Topology::Hex, Topology::Triangle] {
+                for maze_type in MazeType::iter() {
+                    let mut maze = Maze::init_maze_with_topology(8, 6, topology);
+                    maze.set_wrap(true).unwrap();
+                    let walls = raw_passages(&maze, &maze_type, 8);
+
+                    assert_eq!(
+                        check_raw_passages(&maze, &walls),
+                        Ok(()),
+                        "{} on {}",
+                        maze_type,
+                        topology
+                    );
+                    assert!(
+                        walls
+                            .iter()
+                            .any(|(coordinate, direction)| maze.crosses_seam(*coordinate, *direction)),
+                        "{} on {}",
+                        maze_type,
+                        topology
+                    );
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+
+use rand::rngs::StdRng;
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+pub fn random_aldous_broder_maze(maze: &Maze, mut rng: StdRng) -> Vec<(Coordinate, Direction)> {
+    let mut walls_to_break: Vec<(Coordinate, Direction)> = Vec::new();
+    let mut current = maze.random_enabled_cell(&mut rng);
+    let mut visited_nodes = HashSet::from([current]);
+
+    while visited_nodes.len() < maze.enabled_count() {
+        let direction = maze.random_side(current, &mut rng);
+        let new_coordinates = match maze.move_from(&direction, &current, 1) {
+            Ok(coordinates) => coordinates,
+            Err(_) => {
+                continue;
+            }
+        };
+        //only the first entry into a cell carves a passage, which keeps the tree uniform
+        if visited_nodes.insert(new_coordinates) {
+            walls_to_break.push((current, direction));
+        }
+        current = new_coordinates;
+    }
+
+    walls_to_break
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::{maze::maze::Maze, test_utils::all_tiles_reachable::all_tiles_reachable};
+
+    use super::*;
+
+    #[test]
+    fn test_aldous_broder() {
+        for _ in 0..10 {
+            let mut maze = Maze::new(20, 20);
+            maze.set_end((maze.width / 2, maze.height / 2));
+            let walls_to_break =
+                random_aldous_broder_maze(&maze, SeedableRng::from_rng(&mut rand::rng()));
+            maze.break_walls_for_path(walls_to_break);
+
+            assert!(all_tiles_reachable(&maze));
+        }
+    }
+}
@@ -1,46 +1,39 @@
-use std::collections::HashSet;
-
 use rand::{rngs::StdRng, Rng};
 
 use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
 
-pub fn random_binary_maze(maze: &Maze, mut rng: StdRng) -> Vec<(Coordinate, Direction)> {
-    let mut unvisited_nodes: Vec<Coordinate> = (0..maze.width)
-        .flat_map(|x| (0..maze.height).map(move |y| (x, y)))
-        .collect();
+use super::rows::RowSweep;
 
+/// Each cell opens onto a row carved before its own or on along its row, North or East on a
+/// square grid. Cells with neither hang off the cell before them along the row.
+pub fn random_binary_maze(maze: &Maze, mut rng: StdRng) -> Vec<(Coordinate, Direction)> {
+    let sweep = RowSweep::new(maze, &mut rng);
     let mut walls_to_break: Vec<(Coordinate, Direction)> = vec![];
-    let mut visited_nodes = HashSet::from([(maze.width - 1, 0)]);
-    //top right node is considered visited
-    let directions = [Direction::North, Direction::East];
-    while !unvisited_nodes.is_empty() {
-        let mut current = unvisited_nodes.remove(rng.random_range(0..unvisited_nodes.len()));
-        if visited_nodes.contains(&current) {
-            continue;
+    for row in sweep.rows() {
+        //a cell is stuck when nothing from it on along the row can go back a row
+        let mut stuck = vec![false; row.len()];
+        for place in (0..row.len()).rev() {
+            let onward = sweep.next_along(maze, row[place]).is_some() && !stuck[place + 1];
+            stuck[place] = !onward && sweep.sides_back(maze, row[place]).is_empty();
         }
-        let mut new_path: Vec<(Coordinate, Direction)> = Vec::new();
-        loop {
-            let direction = directions[rng.random_range(0..2)];
-
-            let new_coordinates = match maze.move_from(&direction, &current, 1) {
-                Ok(coordinates) => coordinates,
-                Err(_) => {
-                    continue;
-                }
-            };
-
-            new_path.push((current, direction));
-
-            if visited_nodes.contains(&new_coordinates) {
-                break;
+        for (place, &cell) in row.iter().enumerate() {
+            if !maze.is_enabled(cell) {
+                continue;
+            }
+            let mut directions: Vec<Direction> = sweep
+                .sides_back(maze, cell)
+                .into_iter()
+                .map(|(direction, _)| direction)
+                .collect();
+            if sweep.next_along(maze, cell).is_some() && !stuck[place + 1] {
+                directions.push(Direction::East);
+            }
+            if !directions.is_empty() {
+                walls_to_break.push((cell, directions[rng.random_range(0..directions.len())]));
+            } else if sweep.previous_along(maze, cell).is_some() {
+                walls_to_break.push((cell, Direction::West));
             }
-            current = new_coordinates;
-        }
-        for (coords, _) in new_path.iter() {
-            visited_nodes.insert(*coords);
-            unvisited_nodes.retain(|&coord| coord != *coords);
         }
-        walls_to_break.extend(new_path);
     }
 
     walls_to_break
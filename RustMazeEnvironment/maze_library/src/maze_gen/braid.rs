@@ -0,0 +1,104 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+fn is_dead_end(maze: &Maze, coordinate: Coordinate) -> bool {
+    maze.open_sides(coordinate).count() == 1
+}
+
+/// Walls to break so that `fraction` of the dead ends in a carved maze are opened into loops
+pub fn braid_dead_ends(
+    maze: &Maze,
+    fraction: f32,
+    rng: &mut StdRng,
+) -> Vec<(Coordinate, Direction)> {
+    let mut braided = maze.clone();
+    let mut dead_ends: Vec<Coordinate> = (0..maze.width)
+        .flat_map(|x| (0..maze.height).map(move |y| (x, y)))
+        .filter(|coordinate| is_dead_end(maze, *coordinate))
+        .collect();
+    let target = (dead_ends.len() as f32 * fraction.clamp(0.0, 1.0)).round() as usize;
+    dead_ends.shuffle(rng);
+
+    let mut walls_to_break: Vec<(Coordinate, Direction)> = Vec::new();
+    let mut removed = 0;
+    for coordinate in dead_ends {
+        if removed >= target {
+            break;
+        }
+        //opening towards a neighbour may already have fixed this one
+        if !is_dead_end(&braided, coordinate) {
+            continue;
+        }
+        let candidates: Vec<(Direction, Coordinate)> = braided
+            .sides(coordinate)
+            .iter()
+            .copied()
+            .filter(|dir| braided.get_cell(coordinate).walls.contains(dir))
+        .filter_map(|dir| match braided.move_from(&dir, &coordinate, 1) {
+            Ok(neighbour) => Some((dir, neighbour)),
+            Err(_) => None,
+        })
+        .collect();
+        if candidates.is_empty() {
+            continue;
+        }
+        //joining two dead ends removes both with one wall
+        let paired: Vec<&(Direction, Coordinate)> = candidates
+            .iter()
+            .filter(|(_, neighbour)| is_dead_end(&braided, *neighbour))
+            .collect();
+        let direction = if paired.is_empty() {
+            removed += 1;
+            candidates[rng.random_range(0..candidates.len())].0
+        } else {
+            removed += 2;
+            paired[rng.random_range(0..paired.len())].0
+        };
+        braided.break_walls_for_path(vec![(coordinate, direction)]);
+        walls_to_break.push((coordinate, direction));
+    }
+    walls_to_break
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::{
+        maze::maze::Maze, maze_gen::prims::random_prims_maze,
+        test_utils::all_tiles_reachable::all_tiles_reachable,
+    };
+
+    use super::*;
+
+    fn count_dead_ends(maze: &Maze) -> usize {
+        (0..maze.width)
+            .flat_map(|x| (0..maze.height).map(move |y| (x, y)))
+            .filter(|coordinate| is_dead_end(maze, *coordinate))
+            .count()
+    }
+
+    fn carved_maze(seed: u64) -> Maze {
+        let mut maze = Maze::new(15, 15);
+        maze.set_end((maze.width / 2, maze.height / 2));
+        let walls_to_break = random_prims_maze(&maze, StdRng::seed_from_u64(seed));
+        maze.break_walls_for_path(walls_to_break);
+        maze
+    }
+
+    #[test]
+    fn test_braid_fraction() {
+        for (seed, fraction) in [(1, 0.0), (2, 0.5), (3, 1.0)] {
+            let mut maze = carved_maze(seed);
+            let starting_dead_ends = count_dead_ends(&maze) as f32;
+            let walls_to_break = braid_dead_ends(&maze, fraction, &mut StdRng::seed_from_u64(seed));
+            maze.break_walls_for_path(walls_to_break);
+
+            let removed = starting_dead_ends - count_dead_ends(&maze) as f32;
+            assert!(removed >= (starting_dead_ends * fraction).round());
+            assert!(removed <= (starting_dead_ends * fraction).round() + 1.0);
+            assert!(all_tiles_reachable(&maze));
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::{HashSet, VecDeque};
+
+use rand::{rngs::StdRng, Rng};
+
+use crate::{
+    environment::environment::Coordinate,
+    maze::maze::{Door, Key, Maze},
+};
+
+//shortest route from the start to the closest end cell, walked downhill from the goal's distances
+fn route_to_goal(maze: &Maze) -> Vec<Coordinate> {
+    let from_start = maze.distances_from(maze.start);
+    let goal = match maze
+        .end
+        .iter()
+        .filter_map(|end| {
+            from_start[end.1 * maze.width + end.0].map(|distance| (distance, *end))
+        })
+        .min()
+    {
+        Some((_, goal)) => goal,
+        None => return vec![maze.start],
+    };
+    let to_goal = maze.distances_from(goal);
+    let mut route = vec![maze.start];
+    let mut current = maze.start;
+    while current != goal {
+        let remaining = to_goal[current.1 * maze.width + current.0].unwrap_or(0);
+        current = maze
+            .sides(current)
+            .iter()
+            .filter_map(|direction| maze.move_from_with_walls(direction, &current, 1).ok())
+            .find(|next| to_goal[next.1 * maze.width + next.0] == Some(remaining - 1))
+            .expect("a cell on a shortest route has a neighbour one step closer");
+        route.push(current);
+    }
+    route
+}
+
+//cells reachable from the start while the given doors stay shut
+fn reachable_before(maze: &Maze, shut: &[Door]) -> Vec<Coordinate> {
+    let blocked = |from: Coordinate, to: Coordinate| {
+        shut.iter().any(|door| {
+            let beyond = maze.move_from(&door.direction, &door.coordinate, 1).ok();
+            (door.coordinate == from && beyond == Some(to))
+                || (door.coordinate == to && beyond == Some(from))
+        })
+    };
+    let mut reached = vec![maze.start];
+    let mut seen = HashSet::from([maze.start]);
+    let mut queue = VecDeque::from([maze.start]);
+    while let Some(current) = queue.pop_front() {
+        for direction in maze.sides(current) {
+            if let Ok(next) = maze.move_from_with_walls(direction, &current, 1) {
+                if !blocked(current, next) && seen.insert(next) {
+                    reached.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    reached
+}
+
+/// Locks up to `count` passages along the shortest route to the goal, door `k` taking key `k`.
+/// Each key goes somewhere reachable with only the earlier doors opened, off the route when
+/// there is room, so keys have to be fetched in order.
+pub fn place_doors_and_keys(maze: &mut Maze, count: usize, rng: &mut StdRng) {
+    maze.doors.clear();
+    maze.keys.clear();
+    let route = route_to_goal(maze);
+    //the first passage is left open so the first key always has a cell to go in
+    let passages = route.len().saturating_sub(2);
+    let count = count.min(passages);
+    if count == 0 {
+        return;
+    }
+
+    for k in 0..count {
+        let segment_start = 1 + k * passages / count;
+        let segment_end = 1 + (k + 1) * passages / count;
+        let index = rng.random_range(segment_start..segment_end);
+        maze.doors.push(Door {
+            coordinate: route[index],
+            direction: maze
+                .direction_to(route[index], route[index + 1])
+                .expect("route cells are neighbours"),
+            key: k,
+        });
+    }
+
+    let on_route: HashSet<Coordinate> = route.iter().copied().collect();
+    for k in 0..count {
+        let taken: HashSet<Coordinate> = maze.keys.iter().map(|key| key.coordinate).collect();
+        let free: Vec<Coordinate> = reachable_before(maze, &maze.doors[k..])
+            .into_iter()
+            .filter(|cell| {
+                *cell != maze.start && !maze.end.contains(cell) && !taken.contains(cell)
+            })
+            .collect();
+        let off_route: Vec<Coordinate> = free
+            .iter()
+            .copied()
+            .filter(|cell| !on_route.contains(cell))
+            .collect();
+        let choices = if off_route.is_empty() { free } else { off_route };
+        if choices.is_empty() {
+            //nowhere left before this door, keep only the doors that have keys
+            maze.doors.truncate(k);
+            return;
+        }
+        maze.keys.push(Key {
+            coordinate: choices[rng.random_range(0..choices.len())],
+            id: k,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::maze_gen::maze_gen_handler::{
+        select_maze_algorithm, GenerationParameters, MazeType,
+    };
+
+    #[test]
+    fn test_keys_are_reachable_before_their_doors() {
+        let mut maze = Maze::init_maze(12, 12);
+        let walls = select_maze_algorithm(
+            &maze,
+            Some(5),
+            &MazeType::Wilsons,
+            &GenerationParameters::default(),
+        );
+        maze.break_walls_for_path(walls);
+        place_doors_and_keys(&mut maze, 3, &mut StdRng::seed_from_u64(5));
+
+        assert_eq!(maze.doors.len(), 3);
+        assert_eq!(maze.keys.len(), 3);
+        let goal = *maze.end.iter().next().unwrap();
+        for (k, key) in maze.keys.iter().enumerate() {
+            assert_eq!(key.id, k);
+            let reachable = reachable_before(&maze, &maze.doors[k..]);
+            assert!(reachable.contains(&key.coordinate));
+            assert!(!reachable.contains(&goal));
+        }
+        assert!(reachable_before(&maze, &[]).contains(&goal));
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::{BTreeMap, HashSet};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use union_find::{QuickUnionUf, UnionBySize, UnionFind};
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+use super::rows::RowSweep;
+
+/// Joins sets along each row at random, then drops every set at least once onto the rows
+/// still to come. The last row joins whatever sets are left.
+pub fn random_ellers_maze(maze: &Maze, mut rng: StdRng) -> Vec<(Coordinate, Direction)> {
+    let sweep = RowSweep::new(maze, &mut rng);
+    let index = |coordinate: Coordinate| coordinate.1 * maze.width + coordinate.0;
+    let mut sets = QuickUnionUf::<UnionBySize>::new(maze.number_of_cells());
+    let mut walls_to_break: Vec<(Coordinate, Direction)> = Vec::new();
+    //cells of later rows a set has already dropped onto
+    let mut reached = vec![false; maze.number_of_cells()];
+    let rows = sweep.rows();
+
+    for (row_index, row) in rows.iter().enumerate() {
+        let cells: Vec<Coordinate> = row.iter().copied().filter(|c| maze.is_enabled(*c)).collect();
+        let is_last_row = row_index + 1 == rows.len();
+
+        for &cell in &cells {
+            let Ok(next) = maze.move_from(&Direction::East, &cell, 1) else {
+                continue;
+            };
+            if sets.find(index(cell)) == sets.find(index(next)) {
+                continue;
+            }
+            //the last row has to join every remaining set together
+            if !is_last_row && !rng.random_bool(0.5) {
+                continue;
+            }
+            sets.union(index(cell), index(next));
+            walls_to_break.push((cell, Direction::East));
+        }
+
+        if is_last_row {
+            //sets a mask keeps apart along the row meet through the rows already carved
+            for &cell in &cells {
+                for (direction, neighbour) in sweep.sides_back(maze, cell) {
+                    if sets.union(index(cell), index(neighbour)) {
+                        walls_to_break.push((cell, direction));
+                    }
+                }
+            }
+            break;
+        }
+
+        //a set with no way on and nothing dropped ahead of it would be cut off, so it joins
+        //a neighbour instead
+        let later: Vec<Coordinate> = rows[row_index + 1..]
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|c| reached[index(*c)])
+            .collect();
+        let mut ahead: HashSet<usize> = later.iter().map(|c| sets.find(index(*c))).collect();
+        let mut onward: HashSet<usize> = HashSet::new();
+        for &cell in &cells {
+            if !sweep.sides_on(maze, cell).is_empty() {
+                onward.insert(sets.find(index(cell)));
+            }
+        }
+        let mut joined = true;
+        while joined {
+            joined = false;
+            for &cell in &cells {
+                let set = sets.find(index(cell));
+                if onward.contains(&set) || ahead.contains(&set) {
+                    continue;
+                }
+                //a mask can leave a set only its sides back onto the rows already carved
+                let back = sweep.sides_back(maze, cell).into_iter().map(|(d, _)| d);
+                for direction in [Direction::East, Direction::West].into_iter().chain(back) {
+                    let Ok(next) = maze.move_from(&direction, &cell, 1) else {
+                        continue;
+                    };
+                    let other = sets.find(index(next));
+                    if other == set {
+                        continue;
+                    }
+                    sets.union(index(cell), index(next));
+                    walls_to_break.push((cell, direction));
+                    let root = sets.find(index(cell));
+                    if onward.contains(&other) {
+                        onward.insert(root);
+                    }
+                    if ahead.contains(&other) {
+                        ahead.insert(root);
+                    }
+                    joined = true;
+                    break;
+                }
+            }
+        }
+
+        //BTreeMap keeps the iteration order stable so a seed always gives the same maze
+        let mut set_exits: BTreeMap<usize, Vec<(Coordinate, Direction, Coordinate)>> =
+            BTreeMap::new();
+        for &cell in &cells {
+            let exits = set_exits.entry(sets.find(index(cell))).or_default();
+            exits.extend(
+                sweep
+                    .sides_on(maze, cell)
+                    .into_iter()
+                    .map(|(direction, neighbour)| (cell, direction, neighbour)),
+            );
+        }
+
+        for (_, mut exits) in set_exits {
+            if exits.is_empty() {
+                continue;
+            }
+            exits.shuffle(&mut rng);
+            let drops = rng.random_range(1..=exits.len());
+            for &(cell, direction, neighbour) in &exits[..drops] {
+                if sets.union(index(cell), index(neighbour)) {
+                    walls_to_break.push((cell, direction));
+                    reached[index(neighbour)] = true;
+                }
+            }
+        }
+    }
+
+    walls_to_break
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::{maze::maze::Maze, test_utils::all_tiles_reachable::all_tiles_reachable};
+
+    use super::*;
+
+    #[test]
+    fn test_ellers() {
+        for _ in 0..10 {
+            let mut maze = Maze::new(20, 20);
+            maze.set_end((maze.width / 2, maze.height / 2));
+            let walls_to_break =
+                random_ellers_maze(&maze, SeedableRng::from_rng(&mut rand::rng()));
+            maze.break_walls_for_path(walls_to_break);
+
+            assert!(all_tiles_reachable(&maze));
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::sync::{Arc, OnceLock, RwLock};
+
+use rand::rngs::StdRng;
+use strum::IntoEnumIterator;
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+use super::maze_gen_handler::{
+    generate_maze_type, rng_from_seed, with_post_processing, GenerationParameters, MazeType,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorParameter {
+    pub name: String,
+    pub description: String,
+}
+
+/// A maze generation algorithm that can be looked up by name from the CLI and python
+pub trait MazeGenerator: Send + Sync {
+    fn name(&self) -> String;
+
+    /// Parameters the generator reads from `GenerationParameters`
+    fn parameters(&self) -> Vec<GeneratorParameter> {
+        vec![]
+    }
+
+    /// Walls to break in a fully walled copy of `maze`
+    fn generate(
+        &self,
+        maze: &Maze,
+        rng: StdRng,
+        parameters: &GenerationParameters,
+    ) -> Vec<(Coordinate, Direction)>;
+}
+
+struct BuiltinGenerator(MazeType);
+
+impl MazeGenerator for BuiltinGenerator {
+    fn name(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn parameters(&self) -> Vec<GeneratorParameter> {
+        let parameter = |name: &str, description: &str| GeneratorParameter {
+            name: name.to_string(),
+            description: description.to_string(),
+        };
+        match self.0 {
+            MazeType::Sidewinder => vec![parameter(
+                "sidewinder_close_probability",
+                "chance a run is closed at each cell",
+            )],
+            MazeType::GrowingTree => vec![
+                parameter(
+                    "growing_tree_selection",
+                    "newest, oldest, random or mixed active cell selection",
+                ),
+                parameter(
+                    "growing_tree_newest_ratio",
+                    "share of mixed picks taking the newest cell",
+                ),
+            ],
+            MazeType::Rooms => vec![
+                parameter("room_count", "rooms to try to fit in"),
+                parameter("room_min_size", "smallest side of a room in cells"),
+                parameter("room_max_size", "largest side of a room in cells"),
+                parameter("room_doors", "doorways out of each room"),
+                parameter("room_filler", "generator carving the corridors round the rooms"),
+            ],
+            _ => vec![],
+        }
+    }
+
+    fn generate(
+        &self,
+        maze: &Maze,
+        rng: StdRng,
+        parameters: &GenerationParameters,
+    ) -> Vec<(Coordinate, Direction)> {
+        generate_maze_type(maze, rng, &self.0, parameters)
+    }
+}
+
+fn registry() -> &'static RwLock<Vec<Arc<dyn MazeGenerator>>> {
+    static REGISTRY: OnceLock<RwLock<Vec<Arc<dyn MazeGenerator>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        RwLock::new(
+            MazeType::iter()
+                .map(|maze_type| Arc::new(BuiltinGenerator(maze_type)) as Arc<dyn MazeGenerator>)
+                .collect(),
+        )
+    })
+}
+
+/// Lowercases and drops separators so "recursive-backtracker" and "RecursiveBacktracker" match
+fn normalise_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Adds a generator to the registry, replacing any generator with the same name
+pub fn register_generator(generator: impl MazeGenerator + 'static) {
+    let mut generators = registry().write().expect("generator registry poisoned");
+    let name = normalise_name(&generator.name());
+    generators.retain(|existing| normalise_name(&existing.name()) != name);
+    generators.push(Arc::new(generator));
+}
+
+pub fn find_generator(name: &str) -> Option<Arc<dyn MazeGenerator>> {
+    let name = normalise_name(name);
+    registry()
+        .read()
+        .expect("generator registry poisoned")
+        .iter()
+        .find(|generator| normalise_name(&generator.name()) == name)
+        .cloned()
+}
+
+pub fn registered_generators() -> Vec<Arc<dyn MazeGenerator>> {
+    registry()
+        .read()
+        .expect("generator registry poisoned")
+        .clone()
+}
+
+pub fn registered_generator_names() -> Vec<String> {
+    registered_generators()
+        .iter()
+        .map(|generator| generator.name())
+        .collect()
+}
+
+/// Runs a generator followed by the post-processing set in `parameters`
+pub fn run_generator(
+    generator: &dyn MazeGenerator,
+    maze: &Maze,
+    rng: StdRng,
+    parameters: &GenerationParameters,
+) -> Vec<(Coordinate, Direction)> {
+    with_post_processing(maze, rng, parameters, |rng| {
+        generator.generate(maze, rng, parameters)
+    })
+}
+
+/// Runs the named generator, returns None if nothing is registered under that name
+pub fn select_registered_generator(
+    maze: &Maze,
+    rng_seed: Option<u64>,
+    name: &str,
+    parameters: &GenerationParameters,
+) -> Option<Vec<(Coordinate, Direction)>> {
+    find_generator(name)
+        .map(|generator| run_generator(&*generator, maze, rng_from_seed(rng_seed), parameters))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::all_tiles_reachable::all_tiles_reachable;
+
+    use super::*;
+
+    struct CombGenerator;
+
+    impl MazeGenerator for CombGenerator {
+        fn name(&self) -> String {
+            String::from("Comb")
+        }
+
+        fn generate(
+            &self,
+            maze: &Maze,
+            _rng: StdRng,
+            _parameters: &GenerationParameters,
+        ) -> Vec<(Coordinate, Direction)> {
+            let mut walls_to_break: Vec<(Coordinate, Direction)> = (0..maze.width - 1)
+                .map(|x| ((x, 0), Direction::East))
+                .collect();
+            for x in 0..maze.width {
+                walls_to_break.extend((0..maze.height - 1).map(|y| ((x, y), Direction::South)));
+            }
+            walls_to_break
+        }
+    }
+
+    #[test]
+    fn test_builtins_are_registered() {
+        for maze_type in MazeType::iter() {
+            assert!(find_generator(&maze_type.to_string()).is_some());
+        }
+        assert!(find_generator("recursive-backtracker").is_some());
+        assert!(find_generator("not-a-generator").is_none());
+    }
+
+    #[test]
+    fn test_registered_generator_is_found() {
+        register_generator(CombGenerator);
+        assert!(registered_generator_names().contains(&String::from("Comb")));
+
+        let mut maze = Maze::new(6, 6);
+        maze.set_end((3, 3));
+        let walls_to_break =
+            select_registered_generator(&maze, Some(1), "comb", &GenerationParameters::default())
+                .unwrap();
+        maze.break_walls_for_path(walls_to_break);
+
+        assert!(all_tiles_reachable(&maze));
+    }
+}
@@ -1,13 +1,63 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, str::FromStr};
 
+use clap::ValueEnum;
 use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
 
 use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
 
+#[derive(
+    ValueEnum, Clone, Copy, Debug, Hash, Eq, PartialEq, EnumIter, Serialize, Deserialize, Display,
+)]
+pub enum CellSelection {
+    Newest,
+    Oldest,
+    Random,
+    Mixed,
+}
+
+impl FromStr for CellSelection {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "newest" => Ok(CellSelection::Newest),
+            "oldest" => Ok(CellSelection::Oldest),
+            "random" => Ok(CellSelection::Random),
+            "mixed" => Ok(CellSelection::Mixed),
+            _ => Err(()),
+        }
+    }
+}
+
+impl CellSelection {
+    /// Picks an index into the active list, mixed takes the newest cell `newest_ratio` of the time
+    pub fn choose_index(
+        &self,
+        active: &[Coordinate],
+        rng: &mut StdRng,
+        newest_ratio: f32,
+    ) -> usize {
+        match self {
+            CellSelection::Newest => active.len() - 1,
+            CellSelection::Oldest => 0,
+            CellSelection::Random => rng.random_range(0..active.len()),
+            CellSelection::Mixed => {
+                if rng.random_bool(newest_ratio.clamp(0.0, 1.0) as f64) {
+                    active.len() - 1
+                } else {
+                    rng.random_range(0..active.len())
+                }
+            }
+        }
+    }
+}
+
 pub fn growing_tree_maze(
     maze: &Maze,
     mut rng: StdRng,
-    choose_cell: &dyn Fn(&[Coordinate]) -> &Coordinate,
+    choose_cell: &dyn Fn(&[Coordinate], &mut StdRng) -> usize,
 ) -> Vec<(Coordinate, Direction)> {
     let mut active = vec![];
     let mut walls_to_break = vec![];
@@ -15,16 +65,10 @@ pub fn growing_tree_maze(
     let mut visited = HashSet::from([first_end]);
     active.push(first_end);
     while !active.is_empty() {
-        // println!("{:?}", active);
-        // println!("{:?}", visited);
-        let current = *choose_cell(&active);
+        let current_index = choose_cell(&active, &mut rng);
+        let current = active[current_index];
         let mut new_cells = vec![];
-        for dir in [
-            Direction::North,
-            Direction::East,
-            Direction::South,
-            Direction::West,
-        ] {
+        for dir in maze.sides(current).iter().copied() {
             let new_coordinates = match maze.move_from(&dir, &current, 1) {
                 Ok(coordinates) => coordinates,
                 Err(_) => {
@@ -38,13 +82,9 @@ pub fn growing_tree_maze(
 
             new_cells.push((dir, new_coordinates));
         }
-        // println!("{:?}", active);
 
         if new_cells.is_empty() {
-            active = active
-                .into_iter()
-                .filter(|coord| *coord != current)
-                .collect();
+            active.remove(current_index);
             continue;
         }
         let chosen_cell = new_cells.remove(rng.random_range(0..new_cells.len()));
@@ -58,6 +98,7 @@ pub fn growing_tree_maze(
 #[cfg(test)]
 mod tests {
     use rand::SeedableRng;
+    use strum::IntoEnumIterator;
 
     use crate::{maze::maze::Maze, test_utils::all_tiles_reachable::all_tiles_reachable};
 
@@ -69,8 +110,23 @@ mod tests {
             let mut maze = Maze::new(20, 20);
             maze.set_end((maze.width / 2, maze.height / 2));
             let walls_to_break =
-                growing_tree_maze(&mut maze, StdRng::from_rng(&mut rand::rng()), &|list| {
-                    &list[rand::rng().random_range(0..list.len())]
+                growing_tree_maze(&maze, StdRng::from_rng(&mut rand::rng()), &|list, rng| {
+                    rng.random_range(0..list.len())
+                });
+            maze.break_walls_for_path(walls_to_break);
+
+            assert!(all_tiles_reachable(&maze));
+        }
+    }
+
+    #[test]
+    fn test_growing_tree_selections() {
+        for selection in CellSelection::iter() {
+            let mut maze = Maze::new(20, 20);
+            maze.set_end((maze.width / 2, maze.height / 2));
+            let walls_to_break =
+                growing_tree_maze(&maze, StdRng::seed_from_u64(11), &|list, rng| {
+                    selection.choose_index(list, rng, 0.5)
                 });
             maze.break_walls_for_path(walls_to_break);
 
@@ -0,0 +1,86 @@
+use std::collections::HashSet;
+
+use rand::{rngs::StdRng, Rng};
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+fn neighbours_matching(
+    maze: &Maze,
+    current: &Coordinate,
+    visited_nodes: &HashSet<Coordinate>,
+    want_visited: bool,
+) -> Vec<(Direction, Coordinate)> {
+    maze.sides(*current)
+        .iter()
+        .filter_map(|dir| match maze.move_from(dir, current, 1) {
+            Ok(coordinates) => Some((*dir, coordinates)),
+            Err(_) => None,
+        })
+        .filter(|(_, coordinates)| visited_nodes.contains(coordinates) == want_visited)
+        .collect()
+}
+
+pub fn random_hunt_and_kill_maze(maze: &Maze, mut rng: StdRng) -> Vec<(Coordinate, Direction)> {
+    let mut walls_to_break: Vec<(Coordinate, Direction)> = Vec::new();
+    let mut current = maze.random_enabled_cell(&mut rng);
+    let mut visited_nodes = HashSet::from([current]);
+
+    loop {
+        //kill: random walk until the walk boxes itself in
+        let unvisited = neighbours_matching(maze, &current, &visited_nodes, false);
+        if !unvisited.is_empty() {
+            let (direction, new_coordinates) = unvisited[rng.random_range(0..unvisited.len())];
+            walls_to_break.push((current, direction));
+            visited_nodes.insert(new_coordinates);
+            current = new_coordinates;
+            continue;
+        }
+
+        //hunt: scan row by row for an unvisited cell touching the visited region
+        let hunted = (0..maze.height)
+            .flat_map(|y| (0..maze.width).map(move |x| (x, y)))
+            .filter(|coordinates| {
+                maze.is_enabled(*coordinates) && !visited_nodes.contains(coordinates)
+            })
+            .find_map(|coordinates| {
+                let visited = neighbours_matching(maze, &coordinates, &visited_nodes, true);
+                if visited.is_empty() {
+                    return None;
+                }
+                Some((coordinates, visited[rng.random_range(0..visited.len())].0))
+            });
+
+        match hunted {
+            Some((coordinates, direction)) => {
+                walls_to_break.push((coordinates, direction));
+                visited_nodes.insert(coordinates);
+                current = coordinates;
+            }
+            None => break,
+        }
+    }
+
+    walls_to_break
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::{maze::maze::Maze, test_utils::all_tiles_reachable::all_tiles_reachable};
+
+    use super::*;
+
+    #[test]
+    fn test_hunt_and_kill() {
+        for _ in 0..10 {
+            let mut maze = Maze::new(20, 20);
+            maze.set_end((maze.width / 2, maze.height / 2));
+            let walls_to_break =
+                random_hunt_and_kill_maze(&maze, SeedableRng::from_rng(&mut rand::rng()));
+            maze.break_walls_for_path(walls_to_break);
+
+            assert!(all_tiles_reachable(&maze));
+        }
+    }
+}
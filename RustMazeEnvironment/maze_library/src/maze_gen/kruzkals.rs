@@ -1,50 +1,75 @@
-use std::collections::HashSet;
-
-use rand::{rngs::StdRng, seq::IteratorRandom};
+use rand::{rngs::StdRng, Rng};
 use union_find::{QuickUnionUf, UnionBySize, UnionFind};
 
 use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
 
+use super::weave::crossing_passages;
+
 fn unique_coordinate_index(coord: Coordinate, width: usize) -> usize {
     coord.1 * width + coord.0
 }
 
 pub fn random_kruzkals_maze(maze: &Maze, mut rng: StdRng) -> Vec<(Coordinate, Direction)> {
     let mut walls_to_break: Vec<(Coordinate, Direction)> = Vec::new();
-    let mut edge_set: HashSet<(Coordinate, Direction)> = HashSet::new();
+    //a vec rather than a set so the same seed always draws the same edges
+    let mut edge_set: Vec<(Coordinate, Direction)> = Vec::new();
     let mut union_find = QuickUnionUf::<UnionBySize>::new(maze.width * maze.height);
+    //crossings are carved first and the edges round them left out, so the tree weaves
+    for crossing in &maze.crossings {
+        for (coordinate, direction) in crossing_passages(maze, crossing) {
+            if let Ok(neighbour) = maze.neighbour(coordinate, direction) {
+                union_find.union(
+                    unique_coordinate_index(coordinate, maze.width),
+                    unique_coordinate_index(neighbour, maze.width),
+                );
+                walls_to_break.push((coordinate, direction));
+            }
+        }
+    }
 
     //Put all edges into a burlap sack
     for x in 0..maze.width {
         for y in 0..maze.height {
-            if x + 1 < maze.width {
-                edge_set.insert(((x, y), Direction::East));
+            if !maze.is_enabled((x, y)) {
+                continue;
             }
-            if y + 1 < maze.height {
-                edge_set.insert(((x, y), Direction::South));
+            //a wrapped maze also has edges across the seams
+            if x + 1 < maze.width || maze.is_wrapped() {
+                edge_set.push(((x, y), Direction::East));
+            }
+            if y + 1 < maze.height || maze.is_wrapped() {
+                edge_set.push(((x, y), Direction::South));
+            }
+            //hex cells also meet the cell down and to the left
+            if maze.topology.has_side((x, y), Direction::SouthWest)
+                && ((x > 0 && y + 1 < maze.height) || maze.is_wrapped())
+            {
+                edge_set.push(((x, y), Direction::SouthWest));
+            }
+            //and layered cells the stairs to the level above
+            if maze.level_of((x, y)) + 1 < maze.levels() {
+                edge_set.push(((x, y), Direction::Up));
             }
         }
     }
     while !edge_set.is_empty() {
-        let random_edge = match edge_set.iter().choose(&mut rng).cloned() {
-            Some(edge) => edge,
-            None => break,
-        };
+        let random_edge = edge_set.swap_remove(rng.random_range(0..edge_set.len()));
         let new_cell = match maze.move_from(&random_edge.1, &random_edge.0, 1) {
             Ok(coordinates) => coordinates,
             Err(_) => {
                 continue;
             }
         };
+        if maze.crossing_at(random_edge.0).is_some() || maze.crossing_at(new_cell).is_some() {
+            continue;
+        }
         let cell_union_set = unique_coordinate_index(random_edge.0, maze.width);
         let new_cell_union_set = unique_coordinate_index(new_cell, maze.width);
         if union_find.find(cell_union_set) == union_find.find(new_cell_union_set) {
-            edge_set.remove(&random_edge);
             continue;
         }
         union_find.union(cell_union_set, new_cell_union_set);
         walls_to_break.push(random_edge);
-        edge_set.remove(&random_edge);
     }
 
     walls_to_break
@@ -0,0 +1,275 @@
+use std::{fs, str::FromStr};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use union_find::{QuickUnionUf, UnionBySize, UnionFind};
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+use super::weave::crossing_passages;
+
+/// Which cells of the width x height rectangle make up the maze
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MaskShape {
+    /// the ellipse touching all four sides
+    Circle,
+    /// a plus sign with arms a third of the maze across
+    Cross,
+    /// up to this fraction of cells knocked out at random, keeping the rest connected
+    Holes(f32),
+    /// rows of ascii art, `.` and spaces are holes and anything else is a cell
+    Ascii(String),
+}
+
+impl FromStr for MaskShape {
+    type Err = String;
+
+    /// Accepts `circle`, `cross`, `holes:FRACTION` and `file:PATH` to an ascii art file
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, argument) = match s.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (s, None),
+        };
+        match (name.trim().to_lowercase().as_str(), argument) {
+            ("circle", None) => Ok(MaskShape::Circle),
+            ("cross", None) => Ok(MaskShape::Cross),
+            ("holes", Some(fraction)) => fraction
+                .trim()
+                .parse()
+                .map(MaskShape::Holes)
+                .map_err(|e| format!("invalid hole fraction {}: {}", fraction, e)),
+            ("file", Some(path)) => fs::read_to_string(path.trim())
+                .map(MaskShape::Ascii)
+                .map_err(|e| format!("could not read mask {}: {}", path, e)),
+            _ => Err(format!(
+                "unknown mask {}, expected circle, cross, holes:FRACTION or file:PATH",
+                s
+            )),
+        }
+    }
+}
+
+impl MaskShape {
+    /// Enabled flags in grid order for a maze of this size, topology and wrapping
+    pub fn build(&self, maze: &Maze, rng: &mut StdRng) -> Result<Vec<bool>, String> {
+        let (width, height) = (maze.width, maze.height);
+        let cells = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+        match self {
+            MaskShape::Circle => {
+                let (radius_x, radius_y) = (width as f32 / 2.0, height as f32 / 2.0);
+                Ok(cells
+                    .map(|(x, y)| {
+                        let dx = (x as f32 + 0.5 - radius_x) / radius_x;
+                        let dy = (y as f32 + 0.5 - radius_y) / radius_y;
+                        dx * dx + dy * dy <= 1.0
+                    })
+                    .collect())
+            }
+            MaskShape::Cross => {
+                let (arm_x, arm_y) = (width / 3, height / 3);
+                Ok(cells
+                    .map(|(x, y)| {
+                        (arm_x..width - arm_x).contains(&x) || (arm_y..height - arm_y).contains(&y)
+                    })
+                    .collect())
+            }
+            MaskShape::Holes(fraction) => Ok(random_holes(maze, *fraction, rng)),
+            MaskShape::Ascii(art) => parse_ascii(art, width, height),
+        }
+    }
+}
+
+/// Reads one row of art per maze row, short rows are padded with holes
+pub fn parse_ascii(art: &str, width: usize, height: usize) -> Result<Vec<bool>, String> {
+    let rows: Vec<&str> = art.lines().collect();
+    if rows.len() != height {
+        return Err(format!(
+            "mask has {} rows but the maze is {} high",
+            rows.len(),
+            height
+        ));
+    }
+    let mut enabled = Vec::with_capacity(width * height);
+    for (y, row) in rows.iter().enumerate() {
+        let row = row.trim_end();
+        if row.chars().count() > width {
+            return Err(format!("mask row {} is wider than the maze's {} cells", y, width));
+        }
+        enabled.extend(row.chars().map(|c| c != '.' && c != ' '));
+        enabled.resize((y + 1) * width, false);
+    }
+    Ok(enabled)
+}
+
+//knocks cells out in a random order, putting back any that would cut the rest in two
+fn random_holes(shape_of: &Maze, fraction: f32, rng: &mut StdRng) -> Vec<bool> {
+    let mut maze = Maze::new_with_topology(shape_of.width, shape_of.height, shape_of.topology);
+    //wrapping was checked when the maze being masked was made
+    let _ = maze.set_wrap(shape_of.is_wrapped());
+    let _ = maze.set_levels(shape_of.levels());
+    let target = (maze.number_of_cells() as f32 * fraction.clamp(0.0, 1.0)) as usize;
+    let mut enabled = vec![true; maze.number_of_cells()];
+    let mut order: Vec<usize> = (0..enabled.len()).collect();
+    order.shuffle(rng);
+
+    let mut removed = 0;
+    for index in order {
+        if removed >= target || removed + 1 >= enabled.len() {
+            break;
+        }
+        enabled[index] = false;
+        if maze.set_mask(enabled.clone()).is_ok() {
+            removed += 1;
+        } else {
+            enabled[index] = true;
+        }
+    }
+    enabled
+}
+
+/// Drops passages a generator carved into masked cells, through sides the grid lacks or into
+/// crossings, then joins up whatever that split apart with random walls between the pieces so
+/// every enabled cell is reachable again. The row by row generators rely on this when holes
+/// leave cells they could only reach through rows further on.
+pub fn fit_to_mask(
+    maze: &Maze,
+    walls_to_break: Vec<(Coordinate, Direction)>,
+    rng: &mut StdRng,
+) -> Vec<(Coordinate, Direction)> {
+    let index = |coordinate: Coordinate| coordinate.1 * maze.width + coordinate.0;
+    let mut union_find = QuickUnionUf::<UnionBySize>::new(maze.number_of_cells());
+    //what the generator's own passages join, without the crossings
+    let mut carved = QuickUnionUf::<UnionBySize>::new(maze.number_of_cells());
+    let mut kept = Vec::with_capacity(walls_to_break.len());
+    //crossings go in first so the rest of the maze is carved round them
+    for crossing in &maze.crossings {
+        for (coordinate, direction) in crossing_passages(maze, crossing) {
+            let neighbour = maze
+                .neighbour(coordinate, direction)
+                .expect("crossings have a neighbour on every side");
+            union_find.union(index(coordinate), index(neighbour));
+            kept.push((coordinate, direction));
+        }
+    }
+    let touches_crossing = |coordinate: Coordinate, neighbour: Coordinate| {
+        maze.crossing_at(coordinate).is_some() || maze.crossing_at(neighbour).is_some()
+    };
+    for (coordinate, direction) in walls_to_break {
+        if !maze.is_enabled(coordinate) {
+            continue;
+        }
+        if let Ok(neighbour) = maze.move_from(&direction, &coordinate, 1) {
+            if touches_crossing(coordinate, neighbour) {
+                continue;
+            }
+            //loops a generator carves on purpose are kept, ones closed through a crossing are not
+            let joined = union_find.union(index(coordinate), index(neighbour));
+            if joined || carved.find(index(coordinate)) == carved.find(index(neighbour)) {
+                carved.union(index(coordinate), index(neighbour));
+                kept.push((coordinate, direction));
+            }
+        }
+    }
+
+    let mut joins: Vec<(Coordinate, Direction)> = maze
+        .enabled_cells()
+        .flat_map(|coordinate| {
+            [Direction::East, Direction::South, Direction::SouthWest, Direction::Up]
+                .into_iter()
+                .filter(move |direction| {
+                    maze.move_from(direction, &coordinate, 1)
+                        .is_ok_and(|neighbour| !touches_crossing(coordinate, neighbour))
+                })
+                .map(move |direction| (coordinate, direction))
+        })
+        .collect();
+    while !joins.is_empty() {
+        let (coordinate, direction) = joins.swap_remove(rng.random_range(0..joins.len()));
+        let neighbour = maze
+            .move_from(&direction, &coordinate, 1)
+            .expect("joins only lead to enabled cells");
+        if union_find.union(index(coordinate), index(neighbour)) {
+            kept.push((coordinate, direction));
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::{
+        maze_gen::maze_gen_handler::MazeType,
+        test_utils::{
+            all_tiles_reachable::all_tiles_reachable,
+            generator_output::{check_raw_passages, first_invalid_passage, raw_passages},
+        },
+    };
+
+    const ROW_GENERATORS: [MazeType; 3] =
+        [MazeType::BinaryTree, MazeType::Sidewinder, MazeType::Ellers];
+
+    #[test]
+    fn test_parse_ascii_pads_short_rows() {
+        let enabled = parse_ascii("##.\n#\n###", 3, 3).unwrap();
+        assert_eq!(
+            enabled,
+            vec![true, true, false, true, false, false, true, true, true]
+        );
+        assert!(parse_ascii("####\n#\n#", 3, 3).is_err());
+        assert!(parse_ascii("#", 3, 3).is_err());
+    }
+
+    #[test]
+    fn test_every_generator_carves_only_the_enabled_cells() {
+        let mut rng = StdRng::seed_from_u64(6);
+        for shape in [MaskShape::Circle, MaskShape::Cross, MaskShape::Holes(0.3)] {
+            let mut maze = Maze::init_maze(11, 9);
+            maze.set_mask(shape.build(&maze, &mut rng).unwrap()).unwrap();
+            for maze_type in MazeType::iter() {
+                let walls = raw_passages(&maze, &maze_type, 3);
+
+                assert_eq!(
+                    first_invalid_passage(&maze, &walls),
+                    None,
+                    "{} on {:?}",
+                    maze_type,
+                    shape
+                );
+                //holes can leave cells a row generator only reaches through later rows, those
+                //are joined up by fit_to_mask
+                if !matches!(shape, MaskShape::Holes(_)) || !ROW_GENERATORS.contains(&maze_type) {
+                    assert_eq!(
+                        check_raw_passages(&maze, &walls),
+                        Ok(()),
+                        "{} on {:?}",
+                        maze_type,
+                        shape
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fitting_joins_the_pieces_holes_cut_a_row_generator_into() {
+        let mut maze = Maze::init_maze(11, 9);
+        let holes = MaskShape::Holes(0.3).build(&maze, &mut StdRng::seed_from_u64(6));
+        maze.set_mask(holes.unwrap()).unwrap();
+        for maze_type in ROW_GENERATORS {
+            let walls = raw_passages(&maze, &maze_type, 3);
+            assert!(check_raw_passages(&maze, &walls).is_err(), "{}", maze_type);
+
+            let fitted = fit_to_mask(&maze, walls.clone(), &mut StdRng::seed_from_u64(3));
+            assert!(walls.iter().all(|wall| fitted.contains(wall)), "{}", maze_type);
+            assert_eq!(fitted.len() + 1, maze.enabled_count(), "{}", maze_type);
+            let mut carved = maze.clone();
+            carved.break_walls_for_path(fitted);
+            assert!(all_tiles_reachable(&carved), "{}", maze_type);
+            assert!(carved.validate().is_ok(), "{}", maze_type);
+        }
+    }
+}
@@ -1,14 +1,27 @@
-use clap::ValueEnum;
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use clap::{Args, ValueEnum};
+use rand::{rngs::StdRng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use strum_macros::{Display, EnumIter};
 
-use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+use crate::{
+    direction::Direction, environment::environment::Coordinate, maze::maze::Maze,
+    topology::Topology,
+};
 
 use super::{
-    binary_tree::random_binary_maze, growing_tree::growing_tree_maze,
-    kruzkals::random_kruzkals_maze, wilsons::random_wilson_maze,
+    aldous_broder::random_aldous_broder_maze, binary_tree::random_binary_maze,
+    braid::braid_dead_ends,
+    ellers::random_ellers_maze,
+    growing_tree::{growing_tree_maze, CellSelection},
+    mask::{fit_to_mask, MaskShape},
+    hunt_and_kill::random_hunt_and_kill_maze,
+    kruzkals::random_kruzkals_maze,
+    prims::random_prims_maze,
+    recursive_division::random_recursive_division_maze,
+    rooms::random_rooms_maze,
+    sidewinder::random_sidewinder_maze,
+    wilsons::random_wilson_maze,
 };
 
 #[derive(ValueEnum, Clone, Debug, Hash, Eq, PartialEq, EnumIter,Serialize, Deserialize, Display)]
@@ -18,6 +31,112 @@ pub enum MazeType {
     RecursiveBacktracker,
     Prims,
     BinaryTree,
+    Ellers,
+    AldousBroder,
+    HuntAndKill,
+    Sidewinder,
+    RecursiveDivision,
+    GrowingTree,
+    /// open rectangular rooms with another generator's corridors round them
+    Rooms,
+}
+
+/// Tuning knobs for generators that take more than an rng
+#[derive(Args, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationParameters {
+    /// chance a sidewinder run is closed at each cell, higher gives shorter corridors
+    #[arg(long, default_value_t = 0.5)]
+    pub sidewinder_close_probability: f64,
+    /// how the growing tree generator picks the next active cell
+    #[arg(long, value_enum, default_value_t = CellSelection::Mixed)]
+    pub growing_tree_selection: CellSelection,
+    /// share of mixed picks taking the newest cell, 1.0 acts like a backtracker and 0.0 like prims
+    #[arg(long, default_value_t = 0.5)]
+    pub growing_tree_newest_ratio: f32,
+    /// rooms the rooms generator tries to fit in
+    #[arg(long, default_value_t = 4)]
+    pub room_count: usize,
+    /// smallest side of a room in cells
+    #[arg(long, default_value_t = 3)]
+    pub room_min_size: usize,
+    /// largest side of a room in cells
+    #[arg(long, default_value_t = 5)]
+    pub room_max_size: usize,
+    /// doorways out of each room, more than one adds loops
+    #[arg(long, default_value_t = 2)]
+    pub room_doors: usize,
+    /// generator that carves the corridors round the rooms
+    #[arg(long, value_enum, default_value_t = MazeType::Kruzkals)]
+    pub room_filler: MazeType,
+    /// fraction of dead ends opened into loops after generation, 0.0 keeps the maze perfect
+    #[arg(long, default_value_t = 0.0)]
+    pub braid_fraction: f32,
+    /// locked doors put on the route to the goal once it is placed, each with a key before it
+    #[arg(long, default_value_t = 0)]
+    pub door_count: usize,
+    /// pairs of cells joined by portals, placed after the doors
+    #[arg(long, default_value_t = 0)]
+    pub portal_count: usize,
+    /// highest cost of stepping into a cell on the generated terrain, 1 keeps every step at 1
+    #[arg(long, default_value_t = 1)]
+    pub terrain_max_cost: u8,
+    /// cells between the points of the terrain noise lattice, larger gives broader patches
+    #[arg(long, default_value_t = 4)]
+    pub terrain_scale: usize,
+    /// share of the cells that could hold one turned into over and under crossings before
+    /// generating, 0.0 keeps the maze flat
+    #[arg(long, default_value_t = 0.0)]
+    pub weave_density: f32,
+    /// cells left out of the maze: circle, cross, holes:FRACTION or file:PATH to ascii art
+    #[arg(long)]
+    pub mask: Option<MaskShape>,
+    /// extra name=value parameters read by generators registered outside this crate
+    #[arg(long = "gen-param", value_parser = parse_custom_parameter)]
+    pub custom: Vec<(String, f64)>,
+}
+
+fn parse_custom_parameter(input: &str) -> Result<(String, f64), String> {
+    let (name, value) = input
+        .split_once('=')
+        .ok_or_else(|| format!("expected name=value, got {}", input))?;
+    let value = value
+        .parse::<f64>()
+        .map_err(|e| format!("invalid value for {}: {}", name, e))?;
+    Ok((name.to_string(), value))
+}
+
+impl GenerationParameters {
+    pub fn custom_value(&self, name: &str) -> Option<f64> {
+        self.custom
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+impl Default for GenerationParameters {
+    fn default() -> Self {
+        GenerationParameters {
+            sidewinder_close_probability: 0.5,
+            growing_tree_selection: CellSelection::Mixed,
+            growing_tree_newest_ratio: 0.5,
+            room_count: 4,
+            room_min_size: 3,
+            room_max_size: 5,
+            room_doors: 2,
+            room_filler: MazeType::Kruzkals,
+            braid_fraction: 0.0,
+            door_count: 0,
+            portal_count: 0,
+            terrain_max_cost: 1,
+            terrain_scale: 4,
+            weave_density: 0.0,
+            mask: None,
+            custom: vec![],
+        }
+    }
 }
 
 
@@ -38,27 +157,98 @@ impl FromStr for MazeType {
             "recursivebacktracker" => Ok(MazeType::RecursiveBacktracker),
             "prims" => Ok(MazeType::Prims),
             "binarytree" => Ok(MazeType::BinaryTree),
+            "ellers" => Ok(MazeType::Ellers),
+            "aldousbroder" => Ok(MazeType::AldousBroder),
+            "huntandkill" => Ok(MazeType::HuntAndKill),
+            "sidewinder" => Ok(MazeType::Sidewinder),
+            "recursivedivision" => Ok(MazeType::RecursiveDivision),
+            "growingtree" => Ok(MazeType::GrowingTree),
+            "rooms" => Ok(MazeType::Rooms),
             _ => Err(()),
         }
     }
 }
 
+pub fn rng_from_seed(rng_seed: Option<u64>) -> StdRng {
+    match rng_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(&mut rand::rng()),
+    }
+}
+
 pub fn select_maze_algorithm(
     maze: &Maze,
     rng_seed: Option<u64>,
     algorithm: &MazeType,
+    parameters: &GenerationParameters,
+) -> Vec<(Coordinate, Direction)> {
+    with_post_processing(maze, rng_from_seed(rng_seed), parameters, |rng| {
+        generate_maze_type(maze, rng, algorithm, parameters)
+    })
+}
+
+/// Runs a generator then fits it to the maze's mask and topology and applies any
+/// post-processing asked for in the parameters
+pub fn with_post_processing(
+    maze: &Maze,
+    mut rng: StdRng,
+    parameters: &GenerationParameters,
+    generate: impl FnOnce(StdRng) -> Vec<(Coordinate, Direction)>,
+) -> Vec<(Coordinate, Direction)> {
+    let needs_fitting = maze.is_masked()
+        || maze.topology != Topology::Square
+        || maze.levels() > 1
+        || !maze.crossings.is_empty();
+    if parameters.braid_fraction <= 0.0 && !needs_fitting {
+        return generate(rng);
+    }
+    let mut post_rng = StdRng::from_rng(&mut rng);
+    let mut walls_to_break = generate(rng);
+    //crossings go in here, passages through sides the grid lacks are dropped and pieces a
+    //mask cuts off joined
+    if needs_fitting {
+        walls_to_break = fit_to_mask(maze, walls_to_break, &mut post_rng);
+    }
+    if parameters.braid_fraction > 0.0 {
+        let mut carved = maze.clone();
+        carved.break_walls_for_path(walls_to_break.clone());
+        walls_to_break.extend(braid_dead_ends(
+            &carved,
+            parameters.braid_fraction,
+            &mut post_rng,
+        ));
+    }
+    walls_to_break
+}
+
+pub fn generate_maze_type(
+    maze: &Maze,
+    rng: StdRng,
+    algorithm: &MazeType,
+    parameters: &GenerationParameters,
 ) -> Vec<(Coordinate, Direction)> {
-    let rng = match rng_seed {
-        Some(seed) => StdRng::seed_from_u64(seed),
-        None => StdRng::from_rng(&mut rand::rng()),
-    };
     match algorithm {
         MazeType::Wilsons => random_wilson_maze(maze, rng),
         MazeType::Kruzkals => random_kruzkals_maze(maze, rng),
-        MazeType::RecursiveBacktracker => growing_tree_maze(maze, rng, &|list| list.last().unwrap()),
+        MazeType::RecursiveBacktracker => growing_tree_maze(maze, rng, &|list, rng| {
+            CellSelection::Newest.choose_index(list, rng, 1.0)
+        }),
         MazeType::BinaryTree => random_binary_maze(maze, rng),
-        MazeType::Prims => growing_tree_maze(maze, rng.clone(), &|list| {
-            &list[rng.clone().random_range(0..list.len())]
+        MazeType::Ellers => random_ellers_maze(maze, rng),
+        MazeType::AldousBroder => random_aldous_broder_maze(maze, rng),
+        MazeType::HuntAndKill => random_hunt_and_kill_maze(maze, rng),
+        MazeType::Sidewinder => {
+            random_sidewinder_maze(maze, rng, parameters.sidewinder_close_probability)
+        }
+        MazeType::RecursiveDivision => random_recursive_division_maze(maze, rng),
+        MazeType::Prims => random_prims_maze(maze, rng),
+        MazeType::GrowingTree => growing_tree_maze(maze, rng, &|list, rng| {
+            parameters.growing_tree_selection.choose_index(
+                list,
+                rng,
+                parameters.growing_tree_newest_ratio,
+            )
         }),
+        MazeType::Rooms => random_rooms_maze(maze, rng, parameters),
     }
 }
@@ -3,3 +3,19 @@ pub mod kruzkals;
 pub mod maze_gen_handler;
 pub mod wilsons;
 pub mod binary_tree;
+pub mod ellers;
+pub mod aldous_broder;
+pub mod hunt_and_kill;
+pub mod sidewinder;
+pub mod rows;
+pub mod recursive_division;
+pub mod prims;
+pub mod generator_registry;
+pub mod braid;
+pub mod doors;
+pub mod placement;
+pub mod portals;
+pub mod terrain;
+pub mod mask;
+pub mod weave;
+pub mod rooms;
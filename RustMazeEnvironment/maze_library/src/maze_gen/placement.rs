@@ -0,0 +1,262 @@
+use std::str::FromStr;
+
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    environment::environment::Coordinate,
+    maze::maze::{Maze, MoveError},
+};
+
+//random starts tried before minimum distance falls back to the farthest pair
+const MINIMUM_DISTANCE_ATTEMPTS: usize = 16;
+
+/// Where the start and goal go once a maze has been carved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Placement {
+    /// start in the bottom left corner, goal in the centre. On a layered maze the start is
+    /// on the top level and the goal in the centre of the bottom one
+    #[default]
+    Centre,
+    Fixed {
+        start: Coordinate,
+        goal: Coordinate,
+    },
+    Random,
+    /// start in the bottom left corner, goal in the top right
+    OppositeCorners,
+    /// the two ends of the longest shortest path, exact for perfect mazes
+    FarthestPair,
+    /// a random start with a random goal at least this many steps away
+    MinimumDistance(usize),
+}
+
+fn parse_coordinate(s: &str) -> Option<Coordinate> {
+    let (x, y) = s.split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+impl FromStr for Placement {
+    type Err = ();
+
+    /// Accepts `centre`, `random`, `opposite_corners`, `farthest_pair`,
+    /// `min_distance:N` and `fixed:X,Y:X,Y`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowered = s.to_lowercase().replace('-', "_");
+        let mut parts = lowered.splitn(2, ':');
+        let name = parts.next().unwrap_or_default().trim();
+        let argument = parts.next();
+        match (name, argument) {
+            ("centre" | "center", None) => Ok(Placement::Centre),
+            ("random", None) => Ok(Placement::Random),
+            ("opposite_corners" | "corners", None) => Ok(Placement::OppositeCorners),
+            ("farthest_pair" | "farthest", None) => Ok(Placement::FarthestPair),
+            ("min_distance" | "minimum_distance", Some(distance)) => distance
+                .trim()
+                .parse()
+                .map(Placement::MinimumDistance)
+                .map_err(|_| ()),
+            ("fixed", Some(coordinates)) => {
+                let (start, goal) = coordinates.split_once(':').ok_or(())?;
+                Ok(Placement::Fixed {
+                    start: parse_coordinate(start).ok_or(())?,
+                    goal: parse_coordinate(goal).ok_or(())?,
+                })
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+impl Placement {
+    /// Picks the start and goal cells, the maze should already be carved
+    pub fn choose(
+        &self,
+        maze: &Maze,
+        rng: &mut StdRng,
+    ) -> Result<(Coordinate, Coordinate), MoveError> {
+        let corner_start = maze.nearest_enabled((0, maze.height - 1));
+        match *self {
+            Placement::Centre => Ok((
+                corner_start,
+                maze.nearest_enabled((maze.width / 2, maze.level_height() / 2)),
+            )),
+            Placement::Fixed { start, goal } => {
+                for coordinate in [start, goal] {
+                    if !maze.is_cell(coordinate) {
+                        return Err(MoveError::OutOfBounds);
+                    }
+                }
+                Ok((start, goal))
+            }
+            Placement::Random => {
+                let start = random_cell(maze, rng);
+                let mut goal = random_cell(maze, rng);
+                while goal == start && maze.enabled_count() > 1 {
+                    goal = random_cell(maze, rng);
+                }
+                Ok((start, goal))
+            }
+            Placement::OppositeCorners => {
+                Ok((corner_start, maze.nearest_enabled((maze.width - 1, 0))))
+            }
+            Placement::FarthestPair => Ok(farthest_pair(maze, corner_start)),
+            Placement::MinimumDistance(distance) => {
+                Ok(minimum_distance_pair(maze, distance.max(1), rng))
+            }
+        }
+    }
+}
+
+pub fn place_start_and_goal(
+    maze: &mut Maze,
+    placement: &Placement,
+    rng: &mut StdRng,
+) -> Result<(), MoveError> {
+    let (start, goal) = placement.choose(maze, rng)?;
+    maze.set_starting_point(start, None)?;
+    maze.set_end(goal);
+    Ok(())
+}
+
+/// Adds single cell goal regions on random cells that are not the start or already a goal
+pub fn add_random_goals(maze: &mut Maze, count: usize, rng: &mut StdRng) {
+    let mut free: Vec<Coordinate> = (0..maze.number_of_cells())
+        .map(|index| maze.coordinate_at(index))
+        .filter(|coordinate| *coordinate != maze.start && !maze.end.contains(coordinate))
+        .filter(|coordinate| maze.is_enabled(*coordinate))
+        .collect();
+    for _ in 0..count.min(free.len()) {
+        let goal = free.swap_remove(rng.random_range(0..free.len()));
+        maze.add_goal_region(vec![goal]);
+    }
+}
+
+fn random_cell(maze: &Maze, rng: &mut StdRng) -> Coordinate {
+    loop {
+        let cell = maze.coordinate_at(rng.random_range(0..maze.number_of_cells()));
+        if maze.is_enabled(cell) {
+            return cell;
+        }
+    }
+}
+
+//first cell at the greatest distance, walking the grid in row major order
+fn farthest_from(maze: &Maze, from: Coordinate) -> Coordinate {
+    let mut farthest = (from, 0);
+    for (index, distance) in maze.distances_from(from).into_iter().enumerate() {
+        if let Some(distance) = distance {
+            if distance > farthest.1 {
+                farthest = (maze.coordinate_at(index), distance);
+            }
+        }
+    }
+    farthest.0
+}
+
+//two sweeps find the diameter of a tree, on mazes with loops it is a close lower bound
+fn farthest_pair(maze: &Maze, seed: Coordinate) -> (Coordinate, Coordinate) {
+    let first = farthest_from(maze, seed);
+    (first, farthest_from(maze, first))
+}
+
+fn minimum_distance_pair(
+    maze: &Maze,
+    minimum: usize,
+    rng: &mut StdRng,
+) -> (Coordinate, Coordinate) {
+    for _ in 0..MINIMUM_DISTANCE_ATTEMPTS {
+        let start = random_cell(maze, rng);
+        let far_enough: Vec<Coordinate> = maze
+            .distances_from(start)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, distance)| distance.is_some_and(|distance| distance >= minimum))
+            .map(|(index, _)| maze.coordinate_at(index))
+            .collect();
+        if !far_enough.is_empty() {
+            return (start, far_enough[rng.random_range(0..far_enough.len())]);
+        }
+    }
+    //nothing far enough from the starts tried, the diameter is the best on offer
+    farthest_pair(maze, maze.nearest_enabled((0, maze.height - 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::direction::Direction;
+
+    //a single corridor snaking through a 3x3 grid from (0, 0) to (2, 2)
+    fn serpentine() -> Maze {
+        let mut maze = Maze::new(3, 3);
+        maze.break_walls_for_path(vec![
+            ((0, 0), Direction::East),
+            ((1, 0), Direction::East),
+            ((2, 0), Direction::South),
+            ((2, 1), Direction::West),
+            ((1, 1), Direction::West),
+            ((0, 1), Direction::South),
+            ((0, 2), Direction::East),
+            ((1, 2), Direction::East),
+        ]);
+        maze
+    }
+
+    #[test]
+    fn test_parse_placements() {
+        assert_eq!(Placement::from_str("Farthest-Pair"), Ok(Placement::FarthestPair));
+        assert_eq!(
+            Placement::from_str("min_distance:12"),
+            Ok(Placement::MinimumDistance(12))
+        );
+        assert_eq!(
+            Placement::from_str("fixed:0,1:4,5"),
+            Ok(Placement::Fixed {
+                start: (0, 1),
+                goal: (4, 5)
+            })
+        );
+        assert_eq!(Placement::from_str("fixed:0,1"), Err(()));
+    }
+
+    #[test]
+    fn test_strategies_place_start_and_goal() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut maze = serpentine();
+
+        place_start_and_goal(&mut maze, &Placement::FarthestPair, &mut rng).unwrap();
+        let ends = [maze.start, *maze.end.iter().next().unwrap()];
+        assert!(ends.contains(&(0, 0)) && ends.contains(&(2, 2)));
+
+        place_start_and_goal(&mut maze, &Placement::MinimumDistance(6), &mut rng).unwrap();
+        let goal = *maze.end.iter().next().unwrap();
+        assert!(maze.distances_from(maze.start)[goal.1 * 3 + goal.0].unwrap() >= 6);
+
+        place_start_and_goal(&mut maze, &Placement::OppositeCorners, &mut rng).unwrap();
+        assert_eq!((maze.start, maze.end.clone()), ((0, 2), [(2, 0)].into()));
+
+        let outside = Placement::Fixed {
+            start: (0, 0),
+            goal: (3, 3),
+        };
+        assert_eq!(
+            place_start_and_goal(&mut maze, &outside, &mut rng),
+            Err(MoveError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_centre_goal_sits_in_the_middle_of_a_level() {
+        let mut maze = Maze::new(5, 15);
+        maze.set_levels(3).unwrap();
+        let (start, goal) = Placement::Centre
+            .choose(&maze, &mut StdRng::seed_from_u64(0))
+            .unwrap();
+
+        assert_eq!((start, goal), ((0, 14), (2, 2)));
+        assert_eq!((maze.level_of(start), maze.level_of(goal)), (2, 0));
+    }
+}
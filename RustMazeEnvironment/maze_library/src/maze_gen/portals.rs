@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+
+use rand::{rngs::StdRng, Rng};
+
+use crate::{
+    environment::environment::Coordinate,
+    maze::maze::{Maze, Portal},
+};
+
+/// Links up to `count` pairs of random cells with portals, leaving the start, goals, keys and
+/// door cells alone. Stepping onto either end of a pair moves on to the other end.
+pub fn place_portals(maze: &mut Maze, count: usize, rng: &mut StdRng) {
+    maze.portals.clear();
+    let taken: HashSet<Coordinate> = maze.special_cells().into_iter().collect();
+    let mut free: Vec<Coordinate> = (0..maze.number_of_cells())
+        .map(|index| maze.coordinate_at(index))
+        .filter(|cell| *cell != maze.start && !maze.end.contains(cell) && !taken.contains(cell))
+        .filter(|cell| maze.is_enabled(*cell))
+        .collect();
+    for _ in 0..count.min(free.len() / 2) {
+        let a = free.swap_remove(rng.random_range(0..free.len()));
+        let b = free.swap_remove(rng.random_range(0..free.len()));
+        maze.portals.push(Portal { a, b });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_portals_pair_distinct_free_cells() {
+        let mut maze = Maze::init_maze(4, 4);
+        place_portals(&mut maze, 20, &mut StdRng::seed_from_u64(2));
+
+        //the start and the goal leave fourteen cells, enough for seven pairs
+        assert_eq!(maze.portals.len(), 7);
+        let ends: HashSet<Coordinate> = maze
+            .portals
+            .iter()
+            .flat_map(|portal| [portal.a, portal.b])
+            .collect();
+        assert_eq!(ends.len(), 14);
+        assert!(!ends.contains(&maze.start));
+        assert!(maze.end.iter().all(|end| !ends.contains(end)));
+        let portal = maze.portals[0];
+        assert_eq!(maze.portal_partner(portal.a), Some(portal.b));
+        assert_eq!(maze.portal_partner(portal.b), Some(portal.a));
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+
+use rand::{rngs::StdRng, Rng};
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+fn push_frontier_edges(
+    maze: &Maze,
+    cell: Coordinate,
+    visited_nodes: &HashSet<Coordinate>,
+    frontier: &mut Vec<(Coordinate, Direction)>,
+) {
+    for dir in maze.sides(cell) {
+        match maze.move_from(dir, &cell, 1) {
+            Ok(coordinates) if !visited_nodes.contains(&coordinates) => {
+                frontier.push((cell, *dir));
+            }
+            _ => continue,
+        }
+    }
+}
+
+pub fn random_prims_maze(maze: &Maze, mut rng: StdRng) -> Vec<(Coordinate, Direction)> {
+    let mut walls_to_break: Vec<(Coordinate, Direction)> = Vec::new();
+    let start = maze.random_enabled_cell(&mut rng);
+    let mut visited_nodes = HashSet::from([start]);
+    //every edge leading out of the tree, picked uniformly at random each step
+    let mut frontier: Vec<(Coordinate, Direction)> = Vec::new();
+    push_frontier_edges(maze, start, &visited_nodes, &mut frontier);
+
+    while !frontier.is_empty() {
+        let (cell, dir) = frontier.swap_remove(rng.random_range(0..frontier.len()));
+        let new_coordinates = match maze.move_from(&dir, &cell, 1) {
+            Ok(coordinates) => coordinates,
+            Err(_) => continue,
+        };
+        if !visited_nodes.insert(new_coordinates) {
+            continue;
+        }
+        walls_to_break.push((cell, dir));
+        push_frontier_edges(maze, new_coordinates, &visited_nodes, &mut frontier);
+    }
+
+    walls_to_break
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::{maze::maze::Maze, test_utils::all_tiles_reachable::all_tiles_reachable};
+
+    use super::*;
+
+    #[test]
+    fn test_prims() {
+        for _ in 0..10 {
+            let mut maze = Maze::new(20, 20);
+            maze.set_end((maze.width / 2, maze.height / 2));
+            let walls_to_break =
+                random_prims_maze(&maze, SeedableRng::from_rng(&mut rand::rng()));
+            maze.break_walls_for_path(walls_to_break);
+
+            assert!(all_tiles_reachable(&maze));
+        }
+    }
+
+    #[test]
+    fn test_prims_is_seed_deterministic() {
+        let maze = Maze::new(15, 15);
+        let first = random_prims_maze(&maze, StdRng::seed_from_u64(7));
+        let repeat = random_prims_maze(&maze, StdRng::seed_from_u64(7));
+        let other = random_prims_maze(&maze, StdRng::seed_from_u64(8));
+
+        assert_eq!(first, repeat);
+        assert_ne!(first, other);
+    }
+}
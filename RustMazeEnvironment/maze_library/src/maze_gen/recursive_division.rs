@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use union_find::{QuickUnionUf, UnionBySize, UnionFind};
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+#[derive(Clone, Copy)]
+struct Chamber {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+//the two halves either side of a wall and the passages through it, one of which is kept
+struct Cut {
+    first: Chamber,
+    second: Chamber,
+    gaps: Vec<(Coordinate, Direction)>,
+}
+
+impl Chamber {
+    //positions are taken round the grid so a chamber can run over the seam of a wrapped maze
+    fn contains(&self, maze: &Maze, cell: Coordinate) -> bool {
+        (cell.0 + maze.width - self.x) % maze.width < self.width
+            && (cell.1 + maze.height - self.y) % maze.height < self.height
+    }
+
+    fn cells(&self, maze: &Maze) -> Vec<Coordinate> {
+        (0..self.height)
+            .flat_map(|dy| {
+                (0..self.width)
+                    .map(move |dx| ((self.x + dx) % maze.width, (self.y + dy) % maze.height))
+            })
+            .filter(|cell| maze.is_enabled(*cell))
+            .collect()
+    }
+
+    //passages between cells of the chamber, each given once
+    fn passages(&self, maze: &Maze, cells: &[Coordinate]) -> Vec<(Coordinate, Direction)> {
+        let index = |cell: Coordinate| cell.1 * maze.width + cell.0;
+        cells
+            .iter()
+            .flat_map(|cell| {
+                maze.sides(*cell).iter().filter_map(move |direction| {
+                    maze.move_from(direction, cell, 1)
+                        .ok()
+                        .filter(|n| self.contains(maze, *n) && index(*cell) < index(*n))
+                        .map(|_| (*cell, *direction))
+                })
+            })
+            .collect()
+    }
+
+    fn is_connected(&self, maze: &Maze, cells: &[Coordinate]) -> bool {
+        let Some(&first) = cells.first() else {
+            return false;
+        };
+        let mut seen = HashSet::from([first]);
+        let mut stack = vec![first];
+        while let Some(cell) = stack.pop() {
+            for direction in maze.sides(cell) {
+                if let Ok(n) = maze.move_from(direction, &cell, 1) {
+                    if self.contains(maze, n) && seen.insert(n) {
+                        stack.push(n);
+                    }
+                }
+            }
+        }
+        seen.len() == cells.len()
+    }
+
+    fn split(&self, maze: &Maze, horizontally: bool, offset: usize) -> (Chamber, Chamber) {
+        if horizontally {
+            let first = Chamber {
+                height: offset + 1,
+                ..*self
+            };
+            let second = Chamber {
+                y: (self.y + offset + 1) % maze.height,
+                height: self.height - offset - 1,
+                ..*self
+            };
+            (first, second)
+        } else {
+            let first = Chamber {
+                width: offset + 1,
+                ..*self
+            };
+            let second = Chamber {
+                x: (self.x + offset + 1) % maze.width,
+                width: self.width - offset - 1,
+                ..*self
+            };
+            (first, second)
+        }
+    }
+
+    //a wall after `offset` rows or columns, if it leaves both halves in one piece
+    fn cut(&self, maze: &Maze, horizontally: bool, offset: usize) -> Option<Cut> {
+        let (first, second) = self.split(maze, horizontally, offset);
+        let first_cells = first.cells(maze);
+        if !first.is_connected(maze, &first_cells)
+            || !second.is_connected(maze, &second.cells(maze))
+        {
+            return None;
+        }
+        let gaps: Vec<(Coordinate, Direction)> = first_cells
+            .iter()
+            .flat_map(|cell| {
+                maze.sides(*cell).iter().filter_map(move |direction| {
+                    maze.move_from(direction, cell, 1)
+                        .ok()
+                        .filter(|n| second.contains(maze, *n))
+                        .map(|_| (*cell, *direction))
+                })
+            })
+            .collect();
+        (!gaps.is_empty()).then_some(Cut {
+            first,
+            second,
+            gaps,
+        })
+    }
+}
+
+/// Splits the maze into chambers with a wall holding one gap until no chamber can close a
+/// loop. A wall only goes where both halves stay in one piece round any masked cells, and
+/// follows the rows and columns of the grid whatever the shape of its cells.
+pub fn random_recursive_division_maze(
+    maze: &Maze,
+    mut rng: StdRng,
+) -> Vec<(Coordinate, Direction)> {
+    let mut walls_to_break: Vec<(Coordinate, Direction)> = Vec::new();
+    //a wrapped maze has no edge, so the first chamber starts anywhere and the seams are
+    //walled no more than any other line
+    let (x, y) = if maze.is_wrapped() {
+        (rng.random_range(0..maze.width), rng.random_range(0..maze.height))
+    } else {
+        (0, 0)
+    };
+    let mut chambers = vec![Chamber {
+        x,
+        y,
+        width: maze.width,
+        height: maze.height,
+    }];
+
+    while let Some(chamber) = chambers.pop() {
+        let cells = chamber.cells(maze);
+        let passages = chamber.passages(maze, &cells);
+        //a connected chamber with fewer passages than cells has no loop so is left open
+        if passages.len() < cells.len().max(1) {
+            walls_to_break.extend(passages);
+            continue;
+        }
+        let split_horizontally = match chamber.width.cmp(&chamber.height) {
+            std::cmp::Ordering::Less => true,
+            std::cmp::Ordering::Greater => false,
+            std::cmp::Ordering::Equal => rng.random_bool(0.5),
+        };
+        let mut cut = None;
+        for horizontally in [split_horizontally, !split_horizontally] {
+            let extent = if horizontally {
+                chamber.height
+            } else {
+                chamber.width
+            };
+            if extent < 2 {
+                continue;
+            }
+            let first = rng.random_range(0..extent - 1);
+            cut = (0..extent - 1)
+                .map(|step| (first + step) % (extent - 1))
+                .find_map(|offset| chamber.cut(maze, horizontally, offset));
+            if cut.is_some() {
+                break;
+            }
+        }
+
+        match cut {
+            Some(cut) => {
+                walls_to_break.push(cut.gaps[rng.random_range(0..cut.gaps.len())]);
+                chambers.push(cut.first);
+                chambers.push(cut.second);
+            }
+            //no straight wall keeps both halves whole, so the chamber gets a random tree
+            None => {
+                let index = |cell: Coordinate| cell.1 * maze.width + cell.0;
+                let mut sets = QuickUnionUf::<UnionBySize>::new(maze.number_of_cells());
+                let mut passages = passages;
+                passages.shuffle(&mut rng);
+                for (cell, direction) in passages {
+                    let neighbour = maze
+                        .move_from(&direction, &cell, 1)
+                        .expect("passages are between enabled cells");
+                    if sets.union(index(cell), index(neighbour)) {
+                        walls_to_break.push((cell, direction));
+                    }
+                }
+            }
+        }
+    }
+    walls_to_break
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::{maze::maze::Maze, test_utils::all_tiles_reachable::all_tiles_reachable};
+
+    use super::*;
+
+    #[test]
+    fn test_recursive_division() {
+        for _ in 0..10 {
+            let mut maze = Maze::new(20, 20);
+            maze.set_end((maze.width / 2, maze.height / 2));
+            let walls_to_break =
+                random_recursive_division_maze(&maze, SeedableRng::from_rng(&mut rand::rng()));
+            maze.break_walls_for_path(walls_to_break);
+
+            assert!(all_tiles_reachable(&maze));
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use union_find::{QuickUnionUf, UnionBySize, UnionFind};
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+use super::maze_gen_handler::{generate_maze_type, GenerationParameters, MazeType};
+
+struct Room {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Room {
+    fn contains(&self, coordinate: Coordinate) -> bool {
+        (self.x..self.x + self.width).contains(&coordinate.0)
+            && (self.y..self.y + self.height).contains(&coordinate.1)
+    }
+
+    //rooms keep a cell apart so there is always corridor between them
+    fn crowds(&self, other: &Room) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+
+    fn cells(&self) -> impl Iterator<Item = Coordinate> + '_ {
+        (self.y..self.y + self.height)
+            .flat_map(move |y| (self.x..self.x + self.width).map(move |x| (x, y)))
+    }
+}
+
+//tries a few times per room to find a spot clear of the others, masked cells and crossings
+fn place_rooms(maze: &Maze, parameters: &GenerationParameters, rng: &mut StdRng) -> Vec<Room> {
+    let min_size = parameters.room_min_size.max(1);
+    let max_size = parameters.room_max_size.max(min_size);
+    let level_height = maze.level_height();
+    let mut rooms: Vec<Room> = Vec::new();
+    for _ in 0..parameters.room_count * 20 {
+        if rooms.len() >= parameters.room_count {
+            break;
+        }
+        let width = rng.random_range(min_size..=max_size);
+        let height = rng.random_range(min_size..=max_size);
+        if width > maze.width || height > level_height {
+            continue;
+        }
+        //a room stays on one level
+        let level = rng.random_range(0..maze.levels());
+        let room = Room {
+            x: rng.random_range(0..=maze.width - width),
+            y: level * level_height + rng.random_range(0..=level_height - height),
+            width,
+            height,
+        };
+        if rooms.iter().any(|other| room.crowds(other))
+            || room
+                .cells()
+                .any(|cell| !maze.is_enabled(cell) || maze.crossing_at(cell).is_some())
+        {
+            continue;
+        }
+        rooms.push(room);
+    }
+    rooms
+}
+
+/// Opens up to `room_count` rectangular rooms and fills the space round them with the
+/// `room_filler` generator. Each room gets `room_doors` doorways out, the first of them onto
+/// parts of the maze the room is not yet joined to, so the rooms add loops once they have more
+/// than one door.
+pub fn random_rooms_maze(
+    maze: &Maze,
+    mut rng: StdRng,
+    parameters: &GenerationParameters,
+) -> Vec<(Coordinate, Direction)> {
+    let rooms = place_rooms(maze, parameters, &mut rng);
+    let filler = match &parameters.room_filler {
+        MazeType::Rooms => MazeType::Kruzkals,
+        filler => filler.clone(),
+    };
+    let corridors = generate_maze_type(maze, StdRng::from_rng(&mut rng), &filler, parameters);
+
+    let index = |coordinate: Coordinate| coordinate.1 * maze.width + coordinate.0;
+    let room_of = |coordinate: Coordinate| rooms.iter().position(|room| room.contains(coordinate));
+    let mut union_find = QuickUnionUf::<UnionBySize>::new(maze.number_of_cells());
+    let mut walls_to_break = Vec::new();
+    let mut carve = |union_find: &mut QuickUnionUf<UnionBySize>,
+                     (coordinate, direction): (Coordinate, Direction),
+                     neighbour: Coordinate| {
+        union_find.union(index(coordinate), index(neighbour));
+        walls_to_break.push((coordinate, direction));
+    };
+
+    //every passage between two enabled cells, each pair once
+    let mut edges: Vec<(Coordinate, Direction, Coordinate)> = maze
+        .enabled_cells()
+        .flat_map(|coordinate| {
+            maze.sides(coordinate).iter().filter_map(move |direction| {
+                maze.move_from(direction, &coordinate, 1)
+                    .ok()
+                    .filter(|neighbour| index(coordinate) < index(*neighbour))
+                    .map(|neighbour| (coordinate, *direction, neighbour))
+            })
+        })
+        .filter(|(coordinate, _, neighbour)| {
+            maze.crossing_at(*coordinate).is_none() && maze.crossing_at(*neighbour).is_none()
+        })
+        .collect();
+
+    //the filler's passages into the rooms give way to the rooms' own doors
+    for (coordinate, direction) in corridors {
+        if let Ok(neighbour) = maze.neighbour(coordinate, direction) {
+            if room_of(coordinate).is_none() && room_of(neighbour).is_none() {
+                carve(&mut union_find, (coordinate, direction), neighbour);
+            }
+        }
+    }
+    for (coordinate, direction, neighbour) in edges.iter() {
+        if room_of(*coordinate).is_some() && room_of(*coordinate) == room_of(*neighbour) {
+            carve(&mut union_find, (*coordinate, *direction), *neighbour);
+        }
+    }
+
+    for room in 0..rooms.len() {
+        let mut exits: Vec<(Coordinate, Direction, Coordinate)> = edges
+            .iter()
+            .map(|(coordinate, direction, neighbour)| {
+                if room_of(*coordinate) == Some(room) {
+                    (*coordinate, *direction, *neighbour)
+                } else {
+                    (*neighbour, direction.opposite_direction(), *coordinate)
+                }
+            })
+            .filter(|(coordinate, _, neighbour)| {
+                room_of(*coordinate) == Some(room) && room_of(*neighbour).is_none()
+            })
+            .collect();
+        exits.shuffle(&mut rng);
+        let mut doors = 0;
+        //doors that join something new first, then any to make up the number
+        for joining_only in [true, false] {
+            exits.retain(|(coordinate, direction, neighbour)| {
+                if doors >= parameters.room_doors {
+                    return false;
+                }
+                let new_part =
+                    union_find.find(index(*coordinate)) != union_find.find(index(*neighbour));
+                if joining_only && !new_part {
+                    return true;
+                }
+                carve(&mut union_find, (*coordinate, *direction), *neighbour);
+                doors += 1;
+                false
+            });
+        }
+    }
+
+    //corridor cut off by the rooms is joined back on, through an extra door if it must be
+    edges.shuffle(&mut rng);
+    edges.sort_by_key(|(coordinate, _, neighbour)| {
+        room_of(*coordinate).is_some() || room_of(*neighbour).is_some()
+    });
+    for (coordinate, direction, neighbour) in edges {
+        if union_find.find(index(coordinate)) != union_find.find(index(neighbour)) {
+            carve(&mut union_find, (coordinate, direction), neighbour);
+        }
+    }
+    walls_to_break
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        maze_gen::{maze_gen_handler::select_maze_algorithm, weave::place_crossings},
+        test_utils::all_tiles_reachable::all_tiles_reachable,
+    };
+
+    #[test]
+    fn test_rooms_are_apart_and_on_the_grid() {
+        let maze = Maze::init_maze(20, 20);
+        let parameters = GenerationParameters {
+            room_count: 6,
+            ..GenerationParameters::default()
+        };
+        let rooms = place_rooms(&maze, &parameters, &mut StdRng::seed_from_u64(5));
+        assert!(rooms.len() > 1);
+        for (index, room) in rooms.iter().enumerate() {
+            assert!(room.x + room.width <= 20 && room.y + room.height <= 20);
+            assert!((parameters.room_min_size..=parameters.room_max_size).contains(&room.width));
+            assert!(rooms[index + 1..].iter().all(|other| !room.crowds(other)));
+        }
+    }
+
+    #[test]
+    fn test_rooms_keep_their_loops_round_crossings() {
+        let mut maze = Maze::init_maze(16, 16);
+        place_crossings(&mut maze, 0.1, &mut StdRng::seed_from_u64(1));
+        assert!(!maze.crossings.is_empty());
+        let walls = select_maze_algorithm(
+            &maze,
+            Some(2),
+            &MazeType::Rooms,
+            &GenerationParameters::default(),
+        );
+        maze.break_walls_for_path(walls);
+
+        assert!(all_tiles_reachable(&maze));
+        assert!(maze.validate().is_ok());
+        //the open rooms and their extra doors leave more passages than a spanning tree has
+        let passages = maze
+            .cells()
+            .flat_map(|cell| {
+                [Direction::East, Direction::South]
+                    .into_iter()
+                    .map(move |direction| (cell, direction))
+            })
+            .filter(|(cell, direction)| !cell.walls.contains(direction))
+            .filter(|(cell, direction)| maze.neighbour(cell.coordinate, *direction).is_ok())
+            .count();
+        assert!(passages > maze.number_of_cells());
+    }
+
+    #[test]
+    fn test_rooms_are_open_and_reachable() {
+        for filler in [
+            MazeType::Kruzkals,
+            MazeType::BinaryTree,
+            MazeType::RecursiveDivision,
+        ] {
+            let mut maze = Maze::init_maze(16, 14);
+            let parameters = GenerationParameters {
+                room_filler: filler.clone(),
+                ..GenerationParameters::default()
+            };
+            let walls = select_maze_algorithm(&maze, Some(2), &MazeType::Rooms, &parameters);
+            maze.break_walls_for_path(walls);
+
+            assert!(all_tiles_reachable(&maze), "{}", filler);
+            assert!(maze.validate().is_ok(), "{}", filler);
+            //the middle of a room is open on every side, so every cell there is a decision
+            let open = maze
+                .cells()
+                .filter(|cell| cell.walls.is_empty())
+                .map(|cell| cell.coordinate)
+                .collect::<Vec<_>>();
+            assert!(!open.is_empty(), "{}", filler);
+            let graph = maze.convert_to_weighted_graph(None, true);
+            assert!(
+                open.iter().all(|cell| graph.contains_key(cell)),
+                "{}",
+                filler
+            );
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use rand::{rngs::StdRng, Rng};
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+/// The rows the row by row generators carve, in the order they carve them. A layered maze
+/// has each level carved top to bottom before the level above it. A wrapped maze starts from
+/// a random row and each row from a random column, so the seams fall inside the sweep.
+/// Generators skip masked cells, holes can leave cells they only reach through rows further
+/// on and those pieces are joined by `fit_to_mask`.
+pub struct RowSweep {
+    rows: Vec<Vec<Coordinate>>,
+    //the row of the sweep each cell is in and how far along it
+    places: Vec<(usize, usize)>,
+    width: usize,
+}
+
+impl RowSweep {
+    pub fn new(maze: &Maze, rng: &mut StdRng) -> RowSweep {
+        let first_row = if maze.is_wrapped() {
+            rng.random_range(0..maze.height)
+        } else {
+            0
+        };
+        let rows: Vec<Vec<Coordinate>> = (0..maze.height)
+            .map(|row| {
+                let first_column = if maze.is_wrapped() {
+                    rng.random_range(0..maze.width)
+                } else {
+                    0
+                };
+                let y = (first_row + row) % maze.height;
+                (0..maze.width)
+                    .map(|x| ((first_column + x) % maze.width, y))
+                    .collect()
+            })
+            .collect();
+        let mut places = vec![(0, 0); maze.number_of_cells()];
+        for (row, cells) in rows.iter().enumerate() {
+            for (place, cell) in cells.iter().enumerate() {
+                places[cell.1 * maze.width + cell.0] = (row, place);
+            }
+        }
+        RowSweep {
+            rows,
+            places,
+            width: maze.width,
+        }
+    }
+
+    pub fn rows(&self) -> &[Vec<Coordinate>] {
+        &self.rows
+    }
+
+    fn place(&self, cell: Coordinate) -> (usize, usize) {
+        self.places[cell.1 * self.width + cell.0]
+    }
+
+    /// The next cell along the row, if the East side of `cell` opens onto it
+    pub fn next_along(&self, maze: &Maze, cell: Coordinate) -> Option<Coordinate> {
+        let (row, place) = self.place(cell);
+        let next = *self.rows[row].get(place + 1)?;
+        (maze.move_from(&Direction::East, &cell, 1) == Ok(next)).then_some(next)
+    }
+
+    /// The cell before `cell` along the row, if the West side of `cell` opens onto it
+    pub fn previous_along(&self, maze: &Maze, cell: Coordinate) -> Option<Coordinate> {
+        let (row, place) = self.place(cell);
+        let previous = self.rows[row][place.checked_sub(1)?];
+        (maze.move_from(&Direction::West, &cell, 1) == Ok(previous)).then_some(previous)
+    }
+
+    /// Sides of `cell` onto rows carved before its own, North on a square grid and Down to
+    /// the level below
+    pub fn sides_back(&self, maze: &Maze, cell: Coordinate) -> Vec<(Direction, Coordinate)> {
+        let row = self.place(cell).0;
+        self.sides_where(maze, cell, |other| other < row)
+    }
+
+    /// Sides of `cell` onto rows carved after its own, South on a square grid and Up to the
+    /// level above
+    pub fn sides_on(&self, maze: &Maze, cell: Coordinate) -> Vec<(Direction, Coordinate)> {
+        let row = self.place(cell).0;
+        self.sides_where(maze, cell, |other| other > row)
+    }
+
+    fn sides_where(
+        &self,
+        maze: &Maze,
+        cell: Coordinate,
+        keep: impl Fn(usize) -> bool,
+    ) -> Vec<(Direction, Coordinate)> {
+        maze.sides(cell)
+            .iter()
+            .filter_map(|direction| {
+                maze.move_from(direction, &cell, 1)
+                    .ok()
+                    .filter(|neighbour| keep(self.place(*neighbour).0))
+                    .map(|neighbour| (*direction, neighbour))
+            })
+            .collect()
+    }
+}
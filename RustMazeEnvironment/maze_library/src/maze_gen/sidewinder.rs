@@ -0,0 +1,88 @@
+use rand::{rngs::StdRng, Rng};
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+use super::rows::RowSweep;
+
+/// Carves runs along each row, closing each one with a single passage back onto the rows
+/// already carved. A run with no side back joins the run before it.
+pub fn random_sidewinder_maze(
+    maze: &Maze,
+    mut rng: StdRng,
+    close_probability: f64,
+) -> Vec<(Coordinate, Direction)> {
+    let close_probability = close_probability.clamp(0.0, 1.0);
+    let sweep = RowSweep::new(maze, &mut rng);
+    let mut walls_to_break: Vec<(Coordinate, Direction)> = Vec::new();
+
+    for row in sweep.rows() {
+        let mut run_start = None;
+        //the run's sides back onto rows already carved, the first row has none so it is one
+        //long corridor
+        let mut exits: Vec<(Coordinate, Direction)> = Vec::new();
+        for &cell in row {
+            if !maze.is_enabled(cell) {
+                continue;
+            }
+            let start = *run_start.get_or_insert(cell);
+            exits.extend(
+                sweep
+                    .sides_back(maze, cell)
+                    .into_iter()
+                    .map(|(direction, _)| (cell, direction)),
+            );
+            let at_row_end = sweep.next_along(maze, cell).is_none();
+            if !at_row_end && (exits.is_empty() || !rng.random_bool(close_probability)) {
+                walls_to_break.push((cell, Direction::East));
+                continue;
+            }
+            if !exits.is_empty() {
+                walls_to_break.push(exits[rng.random_range(0..exits.len())]);
+            } else if sweep.previous_along(maze, start).is_some() {
+                walls_to_break.push((start, Direction::West));
+            }
+            run_start = None;
+            exits.clear();
+        }
+    }
+
+    walls_to_break
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::{maze::maze::Maze, test_utils::all_tiles_reachable::all_tiles_reachable};
+
+    use super::*;
+
+    #[test]
+    fn test_sidewinder() {
+        for close_probability in [0.0, 0.25, 0.5, 1.0] {
+            let mut maze = Maze::new(20, 20);
+            maze.set_end((maze.width / 2, maze.height / 2));
+            let walls_to_break = random_sidewinder_maze(
+                &maze,
+                SeedableRng::from_rng(&mut rand::rng()),
+                close_probability,
+            );
+            maze.break_walls_for_path(walls_to_break);
+
+            assert!(all_tiles_reachable(&maze));
+        }
+    }
+
+    #[test]
+    fn test_sidewinder_never_closing_gives_full_rows() {
+        let maze = Maze::new(10, 10);
+        let walls_to_break =
+            random_sidewinder_maze(&maze, SeedableRng::seed_from_u64(3), 0.0);
+        let north_walls = walls_to_break
+            .iter()
+            .filter(|(_, dir)| *dir == Direction::North)
+            .count();
+
+        assert_eq!(north_walls, maze.height - 1);
+    }
+}
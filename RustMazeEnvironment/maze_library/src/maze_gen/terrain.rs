@@ -0,0 +1,66 @@
+use rand::{rngs::StdRng, Rng};
+
+use crate::maze::maze::Maze;
+
+fn lerp(from: f32, to: f32, amount: f32) -> f32 {
+    from + (to - from) * amount
+}
+
+/// Lays costs from 1 to `max_cost` over the maze from a value noise field, random values on a
+/// lattice `scale` cells apart blended in between, so nearby cells cost about the same.
+/// A `max_cost` of 1 clears the terrain.
+pub fn generate_terrain(maze: &mut Maze, max_cost: u8, scale: usize, rng: &mut StdRng) {
+    if max_cost <= 1 {
+        maze.set_terrain(vec![]).expect("an empty terrain always fits");
+        return;
+    }
+    let scale = scale.max(1);
+    let lattice_width = maze.width / scale + 2;
+    let lattice_height = maze.height / scale + 2;
+    let lattice: Vec<f32> = (0..lattice_width * lattice_height)
+        .map(|_| rng.random::<f32>())
+        .collect();
+    let at = |x: usize, y: usize| lattice[y * lattice_width + x];
+
+    let costs = (0..maze.number_of_cells())
+        .map(|index| {
+            let (x, y) = maze.coordinate_at(index);
+            let (left, top) = (x / scale, y / scale);
+            let across = (x % scale) as f32 / scale as f32;
+            let down = (y % scale) as f32 / scale as f32;
+            let value = lerp(
+                lerp(at(left, top), at(left + 1, top), across),
+                lerp(at(left, top + 1), at(left + 1, top + 1), across),
+                down,
+            );
+            //value is below 1 so this never passes max_cost
+            1 + (value * max_cost as f32) as u8
+        })
+        .collect();
+    maze.set_terrain(costs)
+        .expect("terrain is built cell for cell");
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_terrain_is_seeded_and_within_costs() {
+        let mut maze = Maze::init_maze(9, 7);
+        generate_terrain(&mut maze, 5, 3, &mut StdRng::seed_from_u64(8));
+        let mut replay = Maze::init_maze(9, 7);
+        generate_terrain(&mut replay, 5, 3, &mut StdRng::seed_from_u64(8));
+
+        assert_eq!(maze.terrain(), replay.terrain());
+        assert_eq!(maze.terrain().len(), 63);
+        assert!(maze.terrain().iter().all(|cost| (1..=5).contains(cost)));
+        assert!(maze.max_cost() > 1);
+
+        generate_terrain(&mut maze, 1, 3, &mut StdRng::seed_from_u64(8));
+        assert!(maze.terrain().is_empty());
+        assert_eq!(maze.cost((4, 4)), 1);
+    }
+}
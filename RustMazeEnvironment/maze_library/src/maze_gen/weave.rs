@@ -0,0 +1,189 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use union_find::{QuickUnionUf, UnionBySize, UnionFind};
+
+use crate::{
+    direction::Direction,
+    environment::environment::Coordinate,
+    maze::maze::{Crossing, Maze},
+    topology::Topology,
+};
+
+const SQUARE_SIDES: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
+/// The walls a crossing needs broken, its own passage out both sides and the tunnel from the
+/// cell before it to the cell after it
+pub fn crossing_passages(maze: &Maze, crossing: &Crossing) -> [(Coordinate, Direction); 3] {
+    let over = if crossing.under == Direction::East {
+        Direction::North
+    } else {
+        Direction::East
+    };
+    let before = maze
+        .move_from(&crossing.under.opposite_direction(), &crossing.coordinate, 1)
+        .expect("crossings are only placed with a neighbour on every side");
+    [
+        (crossing.coordinate, over),
+        (crossing.coordinate, over.opposite_direction()),
+        (before, crossing.under),
+    ]
+}
+
+/// Turns `density` of the cells that could hold one into crossings, ready for a generator to
+/// carve round. Crossings are never next to each other and their passages never close a loop,
+/// so the maze can still be perfect. Only square cells can cross.
+pub fn place_crossings(maze: &mut Maze, density: f32, rng: &mut StdRng) {
+    maze.crossings.clear();
+    if maze.topology != Topology::Square {
+        return;
+    }
+    let width = maze.width;
+    let index = |coordinate: Coordinate| coordinate.1 * width + coordinate.0;
+    let mut union_find = QuickUnionUf::<UnionBySize>::new(maze.number_of_cells());
+    let mut candidates: Vec<Coordinate> = maze
+        .enabled_cells()
+        .filter(|cell| {
+            SQUARE_SIDES
+                .iter()
+                .all(|direction| maze.move_from(direction, cell, 1).is_ok())
+        })
+        .collect();
+    let target = (candidates.len() as f32 * density.clamp(0.0, 1.0)) as usize;
+    candidates.shuffle(rng);
+
+    for coordinate in candidates {
+        if maze.crossings.len() >= target {
+            break;
+        }
+        let beside = |direction: Direction| {
+            maze.move_from(&direction, &coordinate, 1)
+                .expect("candidates have a neighbour on every side")
+        };
+        //a tunnel coming out into another crossing would have nowhere to go
+        if SQUARE_SIDES
+            .iter()
+            .map(|direction| beside(*direction))
+            .chain([coordinate])
+            .any(|cell| maze.crossing_at(cell).is_some())
+        {
+            continue;
+        }
+        let under = if rng.random_bool(0.5) {
+            Direction::East
+        } else {
+            Direction::South
+        };
+        let over = if under == Direction::East {
+            Direction::North
+        } else {
+            Direction::East
+        };
+        let over_cells = [coordinate, beside(over), beside(over.opposite_direction())];
+        let over_roots = over_cells.map(|cell| union_find.find(index(cell)));
+        let (before, after) = (beside(under.opposite_direction()), beside(under));
+        let (before_root, after_root) = (
+            union_find.find(index(before)),
+            union_find.find(index(after)),
+        );
+        let loops = over_roots[0] == over_roots[1]
+            || over_roots[0] == over_roots[2]
+            || over_roots[1] == over_roots[2]
+            || before_root == after_root
+            || (over_roots.contains(&before_root) && over_roots.contains(&after_root));
+        if loops {
+            continue;
+        }
+        union_find.union(index(over_cells[0]), index(over_cells[1]));
+        union_find.union(index(over_cells[0]), index(over_cells[2]));
+        union_find.union(index(before), index(after));
+        maze.crossings.push(Crossing { coordinate, under });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::{
+        maze::maze::MoveError,
+        maze_gen::maze_gen_handler::{select_maze_algorithm, GenerationParameters, MazeType},
+        test_utils::{all_tiles_reachable::all_tiles_reachable, generator_output::raw_passages},
+    };
+
+    #[test]
+    fn test_tunnels_pass_under_the_crossing_cell() {
+        let mut maze = Maze::new(3, 3);
+        maze.crossings.push(Crossing {
+            coordinate: (1, 1),
+            under: Direction::East,
+        });
+        let passages = crossing_passages(&maze, &maze.crossings[0]);
+        maze.break_walls_for_path(passages.to_vec());
+
+        assert_eq!(maze.move_from_with_walls(&Direction::East, &(0, 1), 1), Ok((2, 1)));
+        assert_eq!(maze.move_from_with_walls(&Direction::South, &(1, 0), 2), Ok((1, 2)));
+        assert_eq!(
+            maze.move_from_with_walls(&Direction::West, &(1, 1), 1),
+            Err(MoveError::InvalidDirection)
+        );
+        assert_eq!(maze.get_cell((1, 1)).under, Some(Direction::East));
+        assert!(maze.walls((1, 1)).contains(&Direction::West));
+        assert_eq!(maze.direction_to((2, 1), (0, 1)), Some(Direction::West));
+
+        //the crossing cell is a straight corridor, the tunnel one edge under it
+        let graph = maze.convert_to_weighted_graph(None, true);
+        assert!(!graph.contains_key(&(1, 1)));
+    }
+
+    #[test]
+    fn test_every_generator_weaves_round_the_crossings() {
+        for maze_type in MazeType::iter() {
+            let mut maze = Maze::init_maze(9, 9);
+            place_crossings(&mut maze, 0.5, &mut StdRng::seed_from_u64(3));
+            assert!(!maze.crossings.is_empty());
+            let carved = raw_passages(&maze, &maze_type, 6);
+            let walls = select_maze_algorithm(
+                &maze,
+                Some(6),
+                &maze_type,
+                &GenerationParameters::default(),
+            );
+
+            //each crossing joins at most three pieces, so only that many of the generator's
+            //own passages away from the crossings can be dropped for it
+            let away = |(coordinate, direction): &&(Coordinate, Direction)| {
+                maze.move_from(direction, coordinate, 1).is_ok_and(|neighbour| {
+                    maze.crossing_at(*coordinate).is_none() && maze.crossing_at(neighbour).is_none()
+                })
+            };
+            let dropped = carved
+                .iter()
+                .filter(away)
+                .filter(|passage| !walls.contains(passage))
+                .count();
+            assert!(dropped <= 3 * maze.crossings.len(), "{}", maze_type);
+
+            maze.break_walls_for_path(walls);
+            assert!(all_tiles_reachable(&maze), "{}", maze_type);
+            assert!(maze.validate().is_ok(), "{}", maze_type);
+            //a spanning tree has one fewer passage than cells, a tunnel is one passage
+            let passages = maze
+                .cells()
+                .flat_map(|cell| {
+                    [Direction::East, Direction::South]
+                        .into_iter()
+                        .map(move |direction| (cell, direction))
+                })
+                .filter(|(cell, direction)| !cell.walls.contains(direction))
+                .filter(|(cell, direction)| maze.neighbour(cell.coordinate, *direction).is_ok())
+                .count();
+            assert_eq!(passages, maze.number_of_cells() - 1, "{}", maze_type);
+        }
+    }
+}
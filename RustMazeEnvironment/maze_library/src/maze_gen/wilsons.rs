@@ -5,6 +5,7 @@ use crate::{direction::Direction, environment::environment::Coordinate, maze::ma
 pub fn random_wilson_maze(maze: &Maze, mut rng: StdRng) -> Vec<(Coordinate, Direction)> {
     let mut unvisited_nodes: Vec<Coordinate> = (0..maze.width)
         .flat_map(|x| (0..maze.height).map(move |y| (x, y)))
+        .filter(|coordinate| maze.is_enabled(*coordinate))
         .collect();
 
     let mut walls_to_break: Vec<(Coordinate, Direction)> = Vec::new();
@@ -17,7 +18,7 @@ pub fn random_wilson_maze(maze: &Maze, mut rng: StdRng) -> Vec<(Coordinate, Dire
         }
         let mut new_path: Vec<(Coordinate, Direction)> = Vec::new();
         loop {
-            let direction = Direction::random(&mut rng);
+            let direction = maze.random_side(current, &mut rng);
             let new_coordinates = match maze.move_from(&direction, &current, 1) {
                 Ok(coordinates) => coordinates,
                 Err(_) => {
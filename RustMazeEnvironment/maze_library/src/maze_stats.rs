@@ -0,0 +1,199 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+/// Structural measurements of a carved maze used to compare generator bias
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MazeStats {
+    pub dead_ends: usize,
+    pub junctions: usize,
+    pub corridors: usize,
+    /// number of corridor segments between dead ends or junctions, keyed by segment length
+    pub corridor_length_histogram: BTreeMap<usize, usize>,
+    /// share of corridor cells that carry straight on
+    pub straightness_ratio: f32,
+    /// share of corridor cells that turn a corner
+    pub turn_ratio: f32,
+    /// steps on the shortest path from the start to the nearest end cell
+    pub solution_length: usize,
+    /// solution length over the manhattan distance it covers
+    pub tortuosity: f32,
+    pub open_walls_by_direction: HashMap<Direction, usize>,
+}
+
+fn open_directions(maze: &Maze, coordinate: Coordinate) -> Vec<Direction> {
+    maze.sides(coordinate)
+        .iter()
+        .copied()
+        .filter(|dir| maze.move_from_with_walls(dir, &coordinate, 1).is_ok())
+        .collect()
+}
+
+fn is_corridor(maze: &Maze, coordinate: Coordinate) -> bool {
+    open_directions(maze, coordinate).len() == 2
+}
+
+fn corridor_length_histogram(maze: &Maze) -> BTreeMap<usize, usize> {
+    let mut traversals: BTreeMap<usize, usize> = BTreeMap::new();
+    for x in 0..maze.width {
+        for y in 0..maze.height {
+            let node = (x, y);
+            if is_corridor(maze, node) {
+                continue;
+            }
+            for start_direction in open_directions(maze, node) {
+                let mut direction = start_direction;
+                let mut current = node;
+                let mut length = 0;
+                loop {
+                    current = maze
+                        .move_from_with_walls(&direction, &current, 1)
+                        .expect("direction was open");
+                    length += 1;
+                    if !is_corridor(maze, current) {
+                        break;
+                    }
+                    direction = open_directions(maze, current)
+                        .into_iter()
+                        .find(|dir| *dir != direction.opposite_direction())
+                        .expect("corridor cells have two openings");
+                }
+                *traversals.entry(length).or_insert(0) += 1;
+            }
+        }
+    }
+    //every segment was walked once from each end
+    traversals
+        .into_iter()
+        .map(|(length, count)| (length, count / 2))
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
+
+fn solution_length(maze: &Maze) -> Option<(usize, Coordinate)> {
+    let mut queue = VecDeque::from([(maze.start, 0)]);
+    let mut visited = HashSet::from([maze.start]);
+    while let Some((current, distance)) = queue.pop_front() {
+        if maze.end.contains(&current) {
+            return Some((distance, current));
+        }
+        for direction in open_directions(maze, current) {
+            let neighbour = maze
+                .move_from_with_walls(&direction, &current, 1)
+                .expect("direction was open");
+            if visited.insert(neighbour) {
+                queue.push_back((neighbour, distance + 1));
+            }
+        }
+    }
+    None
+}
+
+impl MazeStats {
+    pub fn new(maze: &Maze) -> MazeStats {
+        let mut dead_ends = 0;
+        let mut junctions = 0;
+        let mut straight = 0;
+        let mut turns = 0;
+        let mut open_walls_by_direction: HashMap<Direction, usize> =
+            maze.directions().iter().map(|dir| (*dir, 0)).collect();
+
+        for x in 0..maze.width {
+            for y in 0..maze.height {
+                let openings = open_directions(maze, (x, y));
+                for dir in openings.iter() {
+                    *open_walls_by_direction.entry(*dir).or_insert(0) += 1;
+                }
+                match openings.len() {
+                    1 => dead_ends += 1,
+                    2 if openings[0] == openings[1].opposite_direction() => straight += 1,
+                    2 => turns += 1,
+                    n if n > 2 => junctions += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        let corridors = straight + turns;
+        let ratio = |count: usize| {
+            if corridors == 0 {
+                0.0
+            } else {
+                count as f32 / corridors as f32
+            }
+        };
+        let (solution_length, tortuosity) = match solution_length(maze) {
+            Some((length, end)) => {
+                let manhattan = maze.start.0.abs_diff(end.0) + maze.start.1.abs_diff(end.1);
+                (length, length as f32 / manhattan.max(1) as f32)
+            }
+            None => (0, 0.0),
+        };
+
+        MazeStats {
+            dead_ends,
+            junctions,
+            corridors,
+            corridor_length_histogram: corridor_length_histogram(maze),
+            straightness_ratio: ratio(straight),
+            turn_ratio: ratio(turns),
+            solution_length,
+            tortuosity,
+            open_walls_by_direction,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        match serde_json::to_string(self) {
+            Ok(string) => string,
+            Err(e) => e.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serpentine_maze() -> Maze {
+        let mut maze = Maze::new(3, 3);
+        maze.set_end((0, 0));
+        maze.break_walls_for_path(vec![
+            ((0, 0), Direction::East),
+            ((1, 0), Direction::East),
+            ((2, 0), Direction::South),
+            ((2, 1), Direction::West),
+            ((1, 1), Direction::West),
+            ((0, 1), Direction::South),
+            ((0, 2), Direction::East),
+            ((1, 2), Direction::East),
+        ]);
+        maze
+    }
+
+    #[test]
+    fn test_serpentine_stats() {
+        let stats = MazeStats::new(&serpentine_maze());
+
+        assert_eq!(stats.dead_ends, 2);
+        assert_eq!(stats.junctions, 0);
+        assert_eq!(stats.corridors, 7);
+        assert_eq!(stats.corridor_length_histogram, BTreeMap::from([(8, 1)]));
+        assert_eq!(stats.straightness_ratio, 3.0 / 7.0);
+        assert_eq!(stats.turn_ratio, 4.0 / 7.0);
+        assert_eq!(stats.solution_length, 6);
+        assert_eq!(stats.tortuosity, 3.0);
+        assert_eq!(stats.open_walls_by_direction[&Direction::East], 6);
+        assert_eq!(stats.open_walls_by_direction[&Direction::South], 2);
+    }
+
+    #[test]
+    fn test_stats_json_round_trip() {
+        let stats = MazeStats::new(&serpentine_maze());
+        let parsed: MazeStats = serde_json::from_str(&stats.to_json()).unwrap();
+        assert_eq!(parsed, stats);
+    }
+}
@@ -1,13 +1,12 @@
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
 
 use pyo3::{pyclass, pymethods, PyErr, PyResult};
 
 use crate::{
-    constants::constants::NUMBER_OF_INPUT_FEATURES,
+    constants::constants::{DOOR_INPUT_FEATURES, TERRAIN_INPUT_FEATURES},
     direction::Direction,
     environment::environment::{Coordinate, Environment},
-    maze::maze::Maze,
-    maze_gen::maze_gen_handler::{select_maze_algorithm, MazeType},
+    goals::GoalProgress,
 };
 
 use super::environment_observations::{calculate_manhattan_distance, Observation};
@@ -47,14 +46,25 @@ impl ActionResult {
 impl Environment {
     pub fn take_action(&mut self, action: Action) -> (Vec<f32>, f32, bool, bool) {
         let old_location = self.current_location;
-        let dir = Direction::from(action.direction);
+        let dir = self
+            .maze
+            .action_direction(self.current_location, action.direction);
         let old_direction = self.previous_direction;
+        let old_goals = self.goal_progress(action.run);
+        let keys_before = self.keys_held.len();
         let steps_taken = self.move_from_current(&dir, action.run);
+        //whatever the cells walked through cost over flat ground is taken off the reward
+        let extra_cost = self
+            .maze
+            .edge_cost(&dir, &old_location, steps_taken)
+            .saturating_sub(steps_taken);
+        //picking up a key is progress whether exploring or solving
+        let key_bonus = if self.keys_held.len() > keys_before { 2.0 } else { 0.0 };
 
         let (is_done, is_truncated, reward);
         if action.run >= self.config.python_config.mini_explore_runs_per_episode {
             (is_done, is_truncated, reward) =
-                self.calculate_reward_for_solving(old_location, old_direction);
+                self.calculate_reward_for_solving(old_location, old_direction, &old_goals);
         } else {
             (is_done, is_truncated, reward) =
                 self.calculate_reward_for_exploring(old_location, old_direction);
@@ -62,7 +72,7 @@ impl Environment {
 
         ActionResult {
             observation: Observation::new(&self, old_location),
-            reward: (reward * (steps_taken as f32).max(1.0)),
+            reward: (reward * (steps_taken as f32).max(1.0)) - extra_cost as f32 * 0.2 + key_bonus,
             is_done,
             is_truncated,
         }
@@ -76,6 +86,7 @@ impl Environment {
         self.total_steps += self.steps;
         self.steps = 0;
         self.current_location = self.maze.start;
+        self.keys_held.clear();
         Observation::new(&self, self.maze.get_starting_point()).flatten_and_scale_observation(&self)
     }
     pub fn smart_reset(&mut self, run: usize) -> Vec<f32> {
@@ -85,35 +96,39 @@ impl Environment {
         self.total_steps += self.steps;
         self.steps = 0;
         self.current_location = self.maze.start;
+        self.keys_held.clear();
         Observation::new(&self, self.maze.get_starting_point()).flatten_and_scale_observation(&self)
     }
 
     pub fn input_shape(&self) -> usize {
-        NUMBER_OF_INPUT_FEATURES
+        let mut shape = self.maze.input_features();
+        if self.uses_doors() {
+            //one locked flag for every direction a side can face
+            shape += DOOR_INPUT_FEATURES + self.maze.directions().len() - 4;
+        }
+        if self.uses_terrain() {
+            shape += TERRAIN_INPUT_FEATURES;
+        }
+        shape
     }
     pub fn output_shape(&self) -> usize {
-        4
+        self.maze.action_count()
     }
     #[pyo3(signature = (input_algorithm=None))]
-    pub fn reset_and_regenerate(&mut self, input_algorithm: Option<String>) -> Vec<f32> {
-        let mut maze = Maze::init_maze(self.maze.width, self.maze.height);
-        let gen_maze_type = if input_algorithm.is_some() {
-            &MazeType::from_str(&input_algorithm.unwrap()).unwrap()
-        } else {
-            &self.config.python_config.generated_maze_type
-        };
-
-        let walls = select_maze_algorithm(&maze, None, &gen_maze_type);
-        maze.break_walls_for_path(walls);
-        self.weighted_graph = maze.convert_to_weighted_graph(None, true);
-        self.maze = maze;
-        self.current_location = self.maze.get_starting_point();
-        self.visited = HashMap::from([(self.maze.get_starting_point(), 0)]);
-        self.overall_visited = HashMap::from([(self.maze.get_starting_point(), 0)]);
-        self.path_followed = Vec::from([(self.maze.get_starting_point(), 0)]);
-        self.steps = 0;
-        self.total_steps = 0;
-        Observation::new(&self, self.maze.get_starting_point()).flatten_and_scale_observation(&self)
+    pub fn reset_and_regenerate(&mut self, input_algorithm: Option<String>) -> PyResult<Vec<f32>> {
+        let gen_maze_type =
+            input_algorithm.unwrap_or(self.config.python_config.generated_maze_type.clone());
+        let parameters = self.config.python_config.generation_parameters.clone();
+
+        self.regenerate_maze(&gen_maze_type, &parameters)
+            .ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Unknown maze generator {}",
+                    gen_maze_type
+                ))
+            })?;
+        Ok(Observation::new(&self, self.maze.get_starting_point())
+            .flatten_and_scale_observation(&self))
     }
 
     pub fn to_json_python(&self) -> PyResult<String> {
@@ -140,11 +155,14 @@ impl Environment {
         &self,
         old_location: Coordinate,
         old_direction: Option<Direction>,
+        old_goals: &GoalProgress,
     ) -> (bool, bool, f32) {
         let mut is_done = false;
         let mut is_truncated = false;
         let mut reward = 0.0;
-        let end = self.maze.get_perfect_end_centre();
+        let end = old_goals
+            .nearest_target_centre(old_location)
+            .unwrap_or_else(|| self.maze.goal_centre());
         if old_direction.is_some() {
             //This is actually the new direction due to it being caclulated after moving
             let difference = self
@@ -181,9 +199,13 @@ impl Environment {
             is_truncated = true;
         }
 
-        if self.maze.end.contains(&self.current_location) {
+        let goals = self.goal_progress(self.get_current_run());
+        if goals.is_complete() {
             is_done = true;
             reward += 30.0 + 300.0 / self.steps as f32;
+        } else if goals.reached_count() > old_goals.reached_count() {
+            //a goal on the way in an all or ordered task
+            reward += 10.0;
         }
 
         (is_done, is_truncated, reward)
@@ -236,7 +258,7 @@ impl Environment {
         }
 
         let explored_ratio =
-            self.overall_visited.len() as f32 / (self.maze.width * self.maze.height) as f32;
+            self.overall_visited.len() as f32 / self.maze.enabled_count() as f32;
         if explored_ratio > 0.95 {
             is_truncated = true;
         }
@@ -248,3 +270,77 @@ impl Environment {
         (false, is_truncated, reward)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        environment_config::{EnvConfig, PythonConfig},
+        maze_gen::maze_gen_handler::GenerationParameters,
+    };
+
+    use super::*;
+
+    fn step_east_onto(cost: u8) -> f32 {
+        let mut env = Environment::new(EnvConfig::new(10, 10, PythonConfig::default()));
+        env.maze.open_wall((0, 9), Direction::East).unwrap();
+        env.maze.set_cost((1, 9), cost);
+        env.weighted_graph
+            .insert((0, 9), HashMap::from([(Direction::East, 1)]));
+        env.current_location = (0, 9);
+
+        let (_, reward, _, _) = env.take_action(Action {
+            direction: 1,
+            run: 0,
+        });
+        reward
+    }
+
+    #[test]
+    fn test_costly_steps_earn_less_than_flat_ones() {
+        let flat = step_east_onto(1);
+        let costly = step_east_onto(6);
+
+        assert!(flat > 0.0);
+        assert!(costly < flat);
+        assert!((flat - costly - 5.0 * 0.2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_layered_observations_keep_rows_within_a_level() {
+        let config = EnvConfig::new(8, 6, PythonConfig::default()).with_levels(3);
+        let mut env = Environment::new_with_seed(config, Some(4));
+        env.regenerate_maze("Kruzkals", &GenerationParameters::default())
+            .unwrap();
+        let observation = env.reset();
+        assert_eq!(observation.len(), env.input_shape());
+
+        //past the steps and visited flags for the six directions come the agent's column
+        //and row, the goal's column and row, then the agent's and the goal's levels
+        let (row, goal_row) = (observation[13], observation[15]);
+        let (level, goal_level) = (observation[16], observation[17]);
+        assert_eq!(
+            (env.maze.start, env.maze.goal_centre()),
+            ((0, 17), (4.0, 3.0))
+        );
+        assert_eq!((row, goal_row), (1.0, 3.0 / 5.0));
+        assert_eq!((level, goal_level), (1.0, 0.0));
+    }
+
+    #[test]
+    fn test_centre_goal_offset_spans_half_the_grid() {
+        let mut env =
+            Environment::new_with_seed(EnvConfig::new(10, 10, PythonConfig::default()), Some(2));
+        env.regenerate_maze("Kruzkals", &GenerationParameters::default())
+            .unwrap();
+        let observation = env.reset();
+
+        //the goal is five columns right of and four rows up from the corner start
+        assert_eq!(
+            (env.maze.start, env.maze.goal_centre()),
+            ((0, 9), (5.0, 5.0))
+        );
+        let (dx, dy) = (observation[19], observation[20]);
+        assert_eq!(dx, 1.0);
+        assert!((dy - 0.1).abs() < 1e-6);
+    }
+}
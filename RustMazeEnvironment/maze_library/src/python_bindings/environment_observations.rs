@@ -3,8 +3,10 @@ use std::collections::HashMap;
 use pyo3::pyclass;
 
 use crate::{
+    constants::constants::{DOOR_INPUT_FEATURES, TERRAIN_INPUT_FEATURES},
     direction::Direction,
     environment::environment::{Coordinate, Environment},
+    maze_gen::placement::Placement,
 };
 
 #[pyclass]
@@ -33,7 +35,7 @@ pub fn calculate_manhattan_distance(pos1: Coordinate, pos2: (f32, f32)) -> f32 {
 
 impl Observation {
     pub fn new(env: &Environment, previous_location: Coordinate) -> Observation {
-        let end = env.maze.get_perfect_end_centre();
+        let end = env.goal_target();
         Observation {
             previous_direction: env.previous_direction.unwrap_or(Direction::North) as usize,
             manhattan_distance: calculate_manhattan_distance(env.current_location, end),
@@ -43,7 +45,7 @@ impl Observation {
             ),
             available_paths: env.available_paths(),
             current_location: env.current_location,
-            end_node: env.maze.get_perfect_end_centre(),
+            end_node: end,
             previous_location,
             is_exploring: env.config.python_config.mini_explore_runs_per_episode > 0,
         }
@@ -51,17 +53,16 @@ impl Observation {
 
     pub fn flatten_and_scale_observation(&self, env: &Environment) -> Vec<f32> {
         let mut vec = Vec::new();
-        let direction_vec = vec![
-            Direction::North,
-            Direction::East,
-            Direction::South,
-            Direction::West,
-        ];
+        let direction_vec = env.maze.directions();
         for dir in direction_vec.iter() {
             let steps = *self.available_paths.get(dir).unwrap_or(&0) as f32;
             let norm = match dir {
-                Direction::North | Direction::South => env.maze.height as f32,
+                Direction::North | Direction::South => env.maze.level_height() as f32,
                 Direction::East | Direction::West => env.maze.width as f32,
+                Direction::NorthEast | Direction::SouthWest => {
+                    env.maze.width.min(env.maze.height) as f32
+                }
+                Direction::Up | Direction::Down => env.maze.levels() as f32,
             };
             vec.push(steps / norm);
         }
@@ -74,10 +75,18 @@ impl Observation {
             }
         }
 
+        //rows are counted within a level, a layered maze gives the levels separately
+        let level_height = env.maze.level_height() as f32;
+        let row = |y: f32| (y % level_height) / (level_height - 1.0);
         vec.push(self.current_location.0 as f32 / (env.maze.width as f32 - 1.0));
-        vec.push(self.current_location.1 as f32 / (env.maze.height as f32 - 1.0));
+        vec.push(row(self.current_location.1 as f32));
         vec.push(self.end_node.0 / (env.maze.width as f32 - 1.0));
-        vec.push(self.end_node.1 / (env.maze.height as f32 - 1.0));
+        vec.push(row(self.end_node.1));
+        if env.maze.levels() > 1 {
+            let top_level = (env.maze.levels() - 1) as f32;
+            vec.push(env.maze.level_of(self.current_location) as f32 / top_level);
+            vec.push((self.end_node.1 / level_height).floor() / top_level);
+        }
         for dir in direction_vec.iter() {
             vec.push(if *visited_paths.get(dir).unwrap_or(&0) > 0 {
                 1.0
@@ -86,10 +95,19 @@ impl Observation {
             });
         }
         vec.push(self.previous_location.0 as f32 / (env.maze.width as f32 - 1.0));
-        vec.push(self.previous_location.1 as f32 / (env.maze.height as f32 - 1.0));
+        vec.push(row(self.previous_location.1 as f32));
         vec.push(self.manhattan_distance / (env.maze.width + env.maze.height) as f32);
-        vec.push((self.goal_dxdy.0 / (env.maze.width as f32 / 2.0) + 1.0) / 2.0);
-        vec.push((self.goal_dxdy.1 / (env.maze.height as f32 / 2.0) + 1.0) / 2.0);
+        //a lone centre goal is never more than half the grid away, any other goal can be
+        //across the whole of it
+        let centred = env.config.placement == Placement::Centre && env.config.goal_count == 1;
+        let (x_reach, y_reach) = if centred {
+            (env.maze.width as f32 / 2.0, level_height / 2.0)
+        } else {
+            (env.maze.width as f32 - 1.0, level_height - 1.0)
+        };
+        vec.push((self.goal_dxdy.0 / x_reach + 1.0) / 2.0);
+        let dy = self.end_node.1 % level_height - (self.current_location.1 as f32 % level_height);
+        vec.push((dy / y_reach + 1.0) / 2.0);
 
         vec.push(
             if env.get_current_run() >= env.config.python_config.mini_explore_runs_per_episode {
@@ -102,20 +120,70 @@ impl Observation {
         let local_visits = *env.visited.get(&self.current_location).unwrap_or(&0) as f32;
         vec.push((local_visits + 1.0).ln() / 5.0);
         vec.extend(self.get_5x5_features(&env));
+        if env.uses_doors() {
+            vec.extend(self.get_door_features(env));
+        }
+        if env.uses_terrain() {
+            vec.extend(self.get_terrain_features(env));
+        }
         vec
     }
 
+    fn get_terrain_features(&self, env: &Environment) -> Vec<f32> {
+        let mut features = Vec::with_capacity(TERRAIN_INPUT_FEATURES);
+        let max_cost = env.maze.max_cost() as f32;
+        for dy in -2..=2 {
+            for dx in -2..=2 {
+                match env.maze.cell_near(env.current_location, (dx, dy)) {
+                    Some(coord) => features.push(env.maze.cost(coord) as f32 / max_cost),
+                    None => features.push(0.0),
+                }
+            }
+        }
+        features
+    }
+
+    fn get_door_features(&self, env: &Environment) -> Vec<f32> {
+        let mut features = Vec::with_capacity(DOOR_INPUT_FEATURES);
+        features.push(env.keys_held.len() as f32 / env.maze.keys.len().max(1) as f32);
+        for direction in env.maze.directions() {
+            features.push(env.door_locked(env.current_location, *direction) as u8 as f32);
+        }
+
+        for dy in -2..=2 {
+            for dx in -2..=2 {
+                // Doors and keys are only seen in cells that have been visited
+                let coord = match env.maze.cell_near(env.current_location, (dx, dy)) {
+                    Some(coord) if env.overall_visited.contains_key(&coord) => coord,
+                    _ => {
+                        features.extend([0.0, 0.0]);
+                        continue;
+                    }
+                };
+                let has_door = env.maze.doors.iter().any(|door| {
+                    door.coordinate == coord
+                        || env.maze.move_from(&door.direction, &door.coordinate, 1) == Ok(coord)
+                });
+                let has_key = env
+                    .maze
+                    .key_at(coord)
+                    .is_some_and(|key| !env.keys_held.contains(&key));
+                features.extend([has_door as u8 as f32, has_key as u8 as f32]);
+            }
+        }
+        features
+    }
+
     fn get_5x5_features(&self, env: &Environment) -> Vec<f32> {
-        let mut features = Vec::with_capacity(5 * 5 * 7);
+        let extra_directions = &env.maze.directions()[4..];
+        let mut features = Vec::with_capacity(5 * 5 * (7 + extra_directions.len()));
 
         for dy in -2..=2 {
             for dx in -2..=2 {
-                let x = env.current_location.0 as i32 + dx;
-                let y = env.current_location.1 as i32 + dy;
 
-                match env.maze.in_bounds((x, y)) {
-                    true => {
-                        let coord = (x as usize, y as usize);
+                //a wrapped maze shows what lies across the seam, other levels are out of sight
+                match env.maze.cell_near(env.current_location, (dx, dy)) {
+                    Some(coord) => {
                         if env.overall_visited.contains_key(&coord) {
                             let cell = env.maze.get_cell(coord); // -> [bool; 4]
 
@@ -126,9 +194,15 @@ impl Observation {
                                 cell.walls.contains(&Direction::East) as u8 as f32,
                                 cell.walls.contains(&Direction::West) as u8 as f32,
                             ]);
+                            features.extend(
+                                extra_directions
+                                    .iter()
+                                    .map(|dir| cell.walls.contains(dir) as u8 as f32),
+                            );
                         } else {
                             // Mask wall info to 0.0 if not visited
                             features.extend([0.0, 0.0, 0.0, 0.0]);
+                            features.extend(extra_directions.iter().map(|_| 0.0));
                         }
                         features.push(if env.overall_visited.contains_key(&coord) {
                             1.0
@@ -142,10 +216,11 @@ impl Observation {
                             0.0
                         });
                     }
-                    false =>
+                    None =>
                     // Out of bounds: all walls, not visited, out_of_bounds = 1
                     {
                         features.extend([1.0, 1.0, 1.0, 1.0]); // walls
+                        features.extend(extra_directions.iter().map(|_| 1.0));
                         features.push(0.0); // visited
                         features.push(1.0); // out of bounds
                         features.push(0.0);
@@ -166,7 +241,7 @@ impl Observation {
                     *env.visited
                         .get(
                             &env.maze
-                                .move_from(&*d, &env.current_location, *steps)
+                                .destination(&*d, &env.current_location, *steps)
                                 .unwrap(),
                         )
                         .unwrap_or(&0),
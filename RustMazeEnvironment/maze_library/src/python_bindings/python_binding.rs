@@ -1,27 +1,37 @@
 pub mod python_bindings {
-    use std::str::FromStr;
+    use std::{collections::HashMap, str::FromStr};
 
     use pyo3::{
         pyfunction, pymodule,
         types::{PyModule, PyModuleMethods},
-        wrap_pyfunction, Bound, PyResult,
+        wrap_pyfunction, Bound, PyErr, PyResult,
     };
-    use strum::IntoEnumIterator;
 
     use crate::{
         direction::Direction,
-        environment::environment::Environment,
+        environment::environment::{Coordinate, Environment},
         environment_config::{EnvConfig, PythonConfig},
-        maze_gen::maze_gen_handler::{select_maze_algorithm, MazeType},
+        goals::GoalMode,
+        maze_stats::MazeStats,
+        maze_gen::{
+            growing_tree::CellSelection,
+            generator_registry::{
+                find_generator, registered_generator_names, run_generator,
+            },
+            mask::MaskShape,
+            maze_gen_handler::{GenerationParameters, MazeType},
+            placement::Placement,
+        },
         python_bindings::{
             environment_bindings::{Action, ActionResult},
             report_card::ReportCard,
         },
+        topology::Topology,
     };
 
     #[pyfunction(
-        signature = (width, height,gen_algorithm=String::from("kruzkals"), allowed_revisits=50, use_sparse_rewards=false, use_weighted_graph=true, rng_seed=None, mini_exploit_runs_per_episode=2, mini_explore_runs_per_episode=2, exploration_steps=None),
-        text_signature = "(width, height,gen_algorithm='kruzkals', allowed_revisits=50, use_sparse_rewards=False,use_weighted_graph=True, rng_seed=None, mini_exploit_runs_per_episode=2, mini_explore_runs_per_episode=2, exploration_steps=None)"
+        signature = (width, height,gen_algorithm=String::from("kruzkals"), allowed_revisits=50, use_sparse_rewards=false, use_weighted_graph=true, rng_seed=None, mini_exploit_runs_per_episode=2, mini_explore_runs_per_episode=2, exploration_steps=None, sidewinder_close_probability=0.5, growing_tree_selection=String::from("mixed"), growing_tree_newest_ratio=0.5, room_count=4, room_min_size=3, room_max_size=5, room_doors=2, room_filler=String::from("kruzkals"), braid_fraction=0.0, gen_parameters=None, placement=String::from("centre"), goal_mode=String::from("any"), goal_count=1, door_count=0, portal_count=0, terrain_max_cost=1, terrain_scale=4, weave_density=0.0, mask=None, topology=String::from("square"), wrap=false, levels=1),
+        text_signature = "(width, height,gen_algorithm='kruzkals', allowed_revisits=50, use_sparse_rewards=False,use_weighted_graph=True, rng_seed=None, mini_exploit_runs_per_episode=2, mini_explore_runs_per_episode=2, exploration_steps=None, sidewinder_close_probability=0.5, growing_tree_selection='mixed', growing_tree_newest_ratio=0.5, room_count=4, room_min_size=3, room_max_size=5, room_doors=2, room_filler='kruzkals', braid_fraction=0.0, gen_parameters=None, placement='centre', goal_mode='any', goal_count=1, door_count=0, portal_count=0, terrain_max_cost=1, terrain_scale=4, weave_density=0.0, mask=None, topology='square', wrap=False, levels=1)"
     )]
     fn init_environment(
         width: usize,
@@ -34,8 +44,78 @@ pub mod python_bindings {
         mini_exploit_runs_per_episode: usize,
         mini_explore_runs_per_episode: usize,
         exploration_steps: Option<usize>,
+        sidewinder_close_probability: f64,
+        growing_tree_selection: String,
+        growing_tree_newest_ratio: f32,
+        room_count: usize,
+        room_min_size: usize,
+        room_max_size: usize,
+        room_doors: usize,
+        room_filler: String,
+        braid_fraction: f32,
+        gen_parameters: Option<HashMap<String, f64>>,
+        placement: String,
+        goal_mode: String,
+        goal_count: usize,
+        door_count: usize,
+        portal_count: usize,
+        terrain_max_cost: u8,
+        terrain_scale: usize,
+        weave_density: f32,
+        mask: Option<String>,
+        topology: String,
+        wrap: bool,
+        levels: usize,
     ) -> PyResult<Environment> {
-        let gen_algo = MazeType::from_str(&gen_algorithm).unwrap_or(MazeType::Kruzkals);
+        let topology = Topology::from_str(&topology).map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown topology {}, expected square, hex or triangle",
+                topology
+            ))
+        })?;
+        let goal_mode = GoalMode::from_str(&goal_mode).map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown goal mode {}, expected any, all or ordered",
+                goal_mode
+            ))
+        })?;
+        let placement = Placement::from_str(&placement).map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown placement {}, expected centre, random, opposite_corners, farthest_pair, min_distance:N or fixed:X,Y:X,Y",
+                placement
+            ))
+        })?;
+        let room_filler = MazeType::from_str(&room_filler).map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown room filler {}, expected one of the generator names",
+                room_filler
+            ))
+        })?;
+        let gen_algo = find_generator(&gen_algorithm)
+            .map(|generator| generator.name())
+            .unwrap_or(MazeType::Kruzkals.to_string());
+        let generation_parameters = GenerationParameters {
+            sidewinder_close_probability,
+            growing_tree_selection: CellSelection::from_str(&growing_tree_selection)
+                .unwrap_or(CellSelection::Mixed),
+            growing_tree_newest_ratio,
+            room_count,
+            room_min_size,
+            room_max_size,
+            room_doors,
+            room_filler,
+            braid_fraction,
+            door_count,
+            portal_count,
+            terrain_max_cost,
+            terrain_scale,
+            weave_density,
+            mask: mask
+                .map(|mask| MaskShape::from_str(&mask))
+                .transpose()
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+            custom: gen_parameters.unwrap_or_default().into_iter().collect(),
+        };
 
         let config: EnvConfig = EnvConfig::new(
             width,
@@ -48,14 +128,63 @@ pub mod python_bindings {
                 exploration_steps: if exploration_steps.is_some() {
                     exploration_steps.unwrap()
                 } else {
-                    width * height
+                    width * height * levels.max(1)
                 },
                 generated_maze_type: gen_algo.clone(),
+                generation_parameters: generation_parameters.clone(),
             },
+        )
+        .with_placement(placement)
+        .with_goals(goal_mode, goal_count)
+        .with_topology(topology)
+        .with_wrap(wrap)
+        .with_levels(levels);
+        let mut env = Environment::new_with_seed(config, rng_seed);
+        if env.maze.is_wrapped() != wrap {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "A {}x{} {} maze is too small to wrap",
+                width, height, topology
+            )));
+        }
+        if env.maze.levels() != levels.max(1) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Only square mazes that do not wrap can have {} levels",
+                levels
+            )));
+        }
+        if let Some(shape) = &generation_parameters.mask {
+            env.apply_mask(shape)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        }
+        if weave_density > 0.0 {
+            env.add_crossings(weave_density);
+        }
+        let generator = find_generator(&gen_algo).expect("generator looked up from the registry");
+        let walls = run_generator(
+            &*generator,
+            &env.maze,
+            env.rng.next_rng(),
+            &generation_parameters,
         );
-        let mut env = Environment::new(config);
-        let walls = select_maze_algorithm(&env.maze, rng_seed, &gen_algo);
         env.maze.break_walls_for_path(walls);
+        env.place_start_and_goal().map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Placement {:?} does not fit a {}x{} maze",
+                placement, width, height
+            ))
+        })?;
+        if generation_parameters.door_count > 0 {
+            env.add_doors_and_keys(generation_parameters.door_count);
+        }
+        if generation_parameters.portal_count > 0 {
+            env.add_portals(generation_parameters.portal_count);
+        }
+        if generation_parameters.terrain_max_cost > 1 {
+            env.add_terrain(
+                generation_parameters.terrain_max_cost,
+                generation_parameters.terrain_scale,
+            );
+        }
         env.weighted_graph = env.maze.convert_to_weighted_graph(None, use_weighted_graph);
         Ok(env)
     }
@@ -68,23 +197,76 @@ pub mod python_bindings {
         Ok(Action { direction, run })
     }
 
-    /// Formats the sum of two numbers as string.
+    /// Adds loops either by knocking out random walls or by braiding a fraction of the dead ends
     #[pyfunction(
-        signature = (environment),
-        text_signature = "(environment)")]
-    fn make_maze_imperfect(environment: &mut Environment) -> PyResult<()> {
-        let walls_to_break = environment.maze.break_random_walls(15);
-        environment.maze.break_walls_for_path(walls_to_break);
+        signature = (environment, walls_to_remove=15, braid_fraction=None),
+        text_signature = "(environment, walls_to_remove=15, braid_fraction=None)")]
+    fn make_maze_imperfect(
+        environment: &mut Environment,
+        walls_to_remove: usize,
+        braid_fraction: Option<f32>,
+    ) -> PyResult<()> {
+        match braid_fraction {
+            Some(fraction) => environment.braid_dead_ends(fraction),
+            None => environment.break_random_walls(walls_to_remove),
+        }
+        environment.weighted_graph = environment.maze.convert_to_weighted_graph(None, true);
+        Ok(())
+    }
+
+    /// Replaces the goals with these regions, in order for ordered tasks, and rebuilds the graph
+    #[pyfunction(
+        signature = (environment, goals),
+        text_signature = "(environment, goals)")]
+    fn set_goals(environment: &mut Environment, goals: Vec<Vec<Coordinate>>) -> PyResult<()> {
+        if let Some(outside) = goals
+            .iter()
+            .flatten()
+            .find(|coordinate| !environment.maze.is_cell(**coordinate))
+        {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Goal {:?} is outside the maze",
+                outside
+            )));
+        }
+        environment.maze.set_goals(goals);
         environment.weighted_graph = environment.maze.convert_to_weighted_graph(None, true);
         Ok(())
     }
 
+    /// Replaces the step costs with rows of costs, `costs[y][x]`, an empty list makes every
+    /// step cost 1 again
+    #[pyfunction(
+        signature = (environment, costs),
+        text_signature = "(environment, costs)")]
+    fn set_terrain(environment: &mut Environment, costs: Vec<Vec<u8>>) -> PyResult<()> {
+        let (width, height) = (environment.maze.width, environment.maze.height);
+        if !costs.is_empty() && (costs.len() != height || costs.iter().any(|row| row.len() != width)) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Terrain must be {} rows of {} costs",
+                height, width
+            )));
+        }
+        environment
+            .maze
+            .set_terrain(costs.into_iter().flatten().collect())
+            .expect("terrain size was checked");
+        Ok(())
+    }
+
     #[pyfunction(
         signature = (environment),
         text_signature = "(environment)")]
     fn get_score(environment: &mut Environment) -> PyResult<ReportCard> {
         Ok(environment.generate_report_card())
     }
+    #[pyfunction(
+        signature = (environment),
+        text_signature = "(environment)")]
+    fn get_maze_stats(environment: &Environment) -> PyResult<MazeStats> {
+        Ok(MazeStats::new(&environment.maze))
+    }
+
     #[pyfunction(
         signature = (json_str),
         text_signature = "(json_str)")]
@@ -96,7 +278,7 @@ pub mod python_bindings {
         signature = (),
         text_signature = "()")]
     fn maze_generation_algorithms() -> PyResult<Vec<String>> {
-        Ok(MazeType::iter().map(|maze| maze.to_string()).collect())
+        Ok(registered_generator_names())
     }
 
     #[pymodule]
@@ -107,11 +289,15 @@ pub mod python_bindings {
         m.add_function(wrap_pyfunction!(get_score, m)?)?;
         m.add_function(wrap_pyfunction!(maze_generation_algorithms, m)?)?;
         m.add_function(wrap_pyfunction!(report_card_from_json, m)?)?;
+        m.add_function(wrap_pyfunction!(get_maze_stats, m)?)?;
+        m.add_function(wrap_pyfunction!(set_goals, m)?)?;
+        m.add_function(wrap_pyfunction!(set_terrain, m)?)?;
         m.add_class::<Direction>()?;
         m.add_class::<Environment>()?;
         m.add_class::<Action>()?;
         m.add_class::<ActionResult>()?;
         m.add_class::<ReportCard>()?;
+        m.add_class::<MazeStats>()?;
         Ok(())
     }
 }
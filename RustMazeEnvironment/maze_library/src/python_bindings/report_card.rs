@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     environment::environment::{calcualte_score_for_coordinate_vector, Coordinate, Environment},
-    solving_algorithms::dijkstra::dijkstra_solve,
+    solving_algorithms::solve_handler::{solve_goal_route, SolveAlgorithm},
 };
 
 #[pyclass]
@@ -53,7 +53,7 @@ fn calculate_run_visited(
             .map(|(_, value)| *value as f32)
             .sum::<f32>()
             / visited_map.len() as f32,
-        visited_map.len() as f32 / env.maze.number_of_cells() as f32,
+        visited_map.len() as f32 / env.maze.enabled_count() as f32,
     )
 }
 
@@ -94,19 +94,16 @@ impl Environment {
             }
         }
         let (score, _, _) = calcualte_score_for_coordinate_vector(
-            &dijkstra_solve(
-                self,
-                self.maze.start,
-                *self.maze.end.iter().next().expect("Error in end"),
-            ),
+            &solve_goal_route(self, &SolveAlgorithm::Dijkstra),
             &self.weighted_graph,
+            &self.maze,
         );
         ReportCard {
             total_steps: self.total_steps,
             full_turns_done: average(&reverse_counts),
             success_rate_in_exploitation: average(&exits_found),
             total_percentage_explored: self.overall_visited.len() as f32
-                / self.maze.number_of_cells() as f32,
+                / self.maze.enabled_count() as f32,
             dijkstra_shortest_path_score: score,
             walls_hit: average(&hit_counts),
             average_run_score: average(&exploit_runs),
@@ -144,7 +141,7 @@ mod tests {
     use crate::{
         environment::environment:: Environment,
         environment_config::{EnvConfig, PythonConfig},
-        maze_gen::maze_gen_handler::{select_maze_algorithm, MazeType}, solving_algorithms::solve_handler::{select_maze_solve_algorithm, SolveAlgorithm},
+        maze_gen::maze_gen_handler::{select_maze_algorithm, GenerationParameters, MazeType}, solving_algorithms::solve_handler::{select_maze_solve_algorithm, SolveAlgorithm},
     }; // or wherever it's defined
 
     #[test]
@@ -152,7 +149,12 @@ mod tests {
         // Setup a minimal environment that would trigger generate_report_card
         let config = EnvConfig::new(9, 9, PythonConfig::default());
         let mut env = Environment::new(config);
-        let walls = select_maze_algorithm(&env.maze, None, &MazeType::Kruzkals);
+        let walls = select_maze_algorithm(
+            &env.maze,
+            None,
+            &MazeType::Kruzkals,
+            &GenerationParameters::default(),
+        );
         env.maze.break_walls_for_path(walls);
         env.weighted_graph = env.maze.convert_to_weighted_graph(None, true);
         let path = select_maze_solve_algorithm(&env, &SolveAlgorithm::Dijkstra);
@@ -2,7 +2,7 @@ pub mod render {
     use crate::constants::constants::{WINDOW_HEIGHT, WINDOW_WIDTH};
     use crate::environment::environment::{Coordinate, Environment};
     use crate::render_system::render_coloured_mazes::draw_coloured_maze;
-    use crate::render_system::render_maze::draw_maze;
+    use crate::render_system::render_maze::{draw_maze, maze_extent};
     use macroquad::color::YELLOW;
     use macroquad::input::{is_key_pressed, KeyCode};
     use macroquad::text::draw_text;
@@ -27,18 +27,22 @@ pub mod render {
         bools.iter().all(|&b| b)
     }
 
+    /// Steps through the episodes a screenful at a time, `level` shows only that level of
+    /// layered mazes where None puts every level side by side
     pub async fn render_mazes(
         environments: Vec<Environment>,
         cell_size: f32,
         coloured_heatmap: bool,
+        level: Option<usize>,
     ) {
         println!("RUNNNING MAZES {}", environments.len());
         let full_episode = environments[0].maze.number_of_cells();
+        let (cells_across, cells_down) = maze_extent(&environments[0].maze, level);
         let (rows, columns) = calculate_number_of_potential_screens(
             (WINDOW_WIDTH as usize, WINDOW_HEIGHT as usize),
             (
-                (environments[0].maze.width + 2) * cell_size as usize,
-                (environments[0].maze.height + 2) * cell_size as usize,
+                (cells_across + 2) * cell_size as usize,
+                (cells_down + 2) * cell_size as usize,
             ),
         );
 
@@ -100,14 +104,15 @@ pub mod render {
                             draw_coloured_maze(
                                 &environments[env_index],
                                 cell_size,
-                                cell_size * (col * (environments[0].maze.width + 2)) as f32,
-                                cell_size * (row * (environments[0].maze.height + 2)) as f32,
+                                cell_size * (col * (cells_across + 2)) as f32,
+                                cell_size * (row * (cells_down + 2)) as f32,
                                 &environments[env_index].overall_visited,
                                 if environments[env_index].path_followed.len() > 5 {
                                     true
                                 } else {
                                     false
                                 },
+                                level,
                             )
                             .await;
                         } else {
@@ -126,8 +131,9 @@ pub mod render {
                                 cell_size,
                                 &visited_nodes[idx],
                                 step_to_use,
-                                cell_size * (col * (environments[0].maze.width + 2)) as f32,
-                                cell_size * (row * (environments[0].maze.height + 2)) as f32,
+                                cell_size * (col * (cells_across + 2)) as f32,
+                                cell_size * (row * (cells_down + 2)) as f32,
+                                level,
                             )
                             .await;
                             visited_nodes[idx]
@@ -1,10 +1,10 @@
 use std::collections::HashMap;
 
-use macroquad::{color::{Color, BLACK, DARKPURPLE}, shapes::draw_rectangle};
+use macroquad::color::{Color, BLACK, DARKPURPLE};
 
 use crate::{environment::environment::{Coordinate, Environment}, maze::maze::Cell};
 
-use super::render_maze::draw_cell_walls;
+use super::render_maze::{cell_corners, draw_cell_walls, fill_cell, level_shift, shows_cell};
 
 pub async fn draw_coloured_maze(
     environment: &Environment,
@@ -12,24 +12,28 @@ pub async fn draw_coloured_maze(
     x_offset: f32,
     y_offset: f32,
     path_map: &HashMap<Coordinate, usize>,
-    inverse: bool
+    inverse: bool,
+    level: Option<usize>,
 ) {
     let max_steps = path_map.values().max().unwrap_or(&100);
-    for row in &environment.maze.grid {
-        for cell in row {
-            draw_cell_coloured(
-                cell,
-                cell_size,
-                x_offset + 10.0,
-                y_offset + 10.0,
-                path_map,
-                *max_steps,
-                Color::from_rgba(30, 144, 255, 255),
-                environment,
-                inverse
-            )
-            .await;
-        }
+    for cell in environment
+        .maze
+        .cells()
+        .filter(|cell| environment.maze.is_enabled(cell.coordinate))
+        .filter(|cell| shows_cell(&environment.maze, level, cell.coordinate))
+    {
+        draw_cell_coloured(
+            &cell,
+            cell_size,
+            x_offset + 10.0 - level_shift(&environment.maze, level, cell_size),
+            y_offset + 10.0,
+            path_map,
+            *max_steps,
+            Color::from_rgba(30, 144, 255, 255),
+            environment,
+            inverse
+        )
+        .await;
     }
 }
 
@@ -44,9 +48,8 @@ async fn draw_cell_coloured(
     environment: &Environment,
     inverse: bool
 ) {
-    let x = cell.coordinate.0 as f32 * cell_size  + x_offset;
-    let y = cell.coordinate.1 as f32 * cell_size  + y_offset;
     let coordinates = (cell.coordinate.0, cell.coordinate.1);
+    let corners = cell_corners(&environment.maze, coordinates, cell_size, x_offset, y_offset);
     let increased_brightness = 0.2;
     let base_color = if let Some(steps) = path_map.get(&coordinates) {
         // Clamp steps to a maximum for color normalization
@@ -69,19 +72,19 @@ async fn draw_cell_coloured(
             1.0,
         )
     };
-    if cell.walls.len() == 4 {
-        draw_rectangle(x, y, cell_size, cell_size, BLACK);
+    if environment.maze.open_sides(coordinates).next().is_none() {
+        fill_cell(&corners, BLACK);
     } else {
-        draw_rectangle(x, y, cell_size, cell_size, base_color);
+        fill_cell(&corners, base_color);
     }
 
     if environment.maze.end.contains(&coordinates) {
-        draw_rectangle(x, y, cell_size, cell_size, DARKPURPLE); // Change RED to any color you prefer
+        fill_cell(&corners, DARKPURPLE); // Change RED to any color you prefer
     }
 
     if coordinates == environment.maze.start {
-        draw_rectangle(x, y, cell_size, cell_size, DARKPURPLE);
+        fill_cell(&corners, DARKPURPLE);
     }
 
-    draw_cell_walls(cell, cell_size, x, y, 1.0);
+    draw_cell_walls(cell, &environment.maze, &corners, 1.0);
 }
\ No newline at end of file
@@ -1,8 +1,8 @@
 use std::collections::HashSet;
 
-use macroquad::{color::{BLACK, GOLD, GREEN, LIGHTGRAY, PINK, RED, WHITE, YELLOW}, shapes::{draw_line, draw_rectangle}, text::draw_text, window::{clear_background, next_frame}};
+use macroquad::{color::{Color, BLACK, BLUE, GOLD, GREEN, LIGHTGRAY, ORANGE, PINK, PURPLE, RED, SKYBLUE, VIOLET, WHITE, YELLOW}, math::Vec2, shapes::{draw_circle, draw_line, draw_rectangle, draw_triangle}, text::draw_text, window::{clear_background, next_frame}};
 
-use crate::{direction::Direction, environment::environment::{Coordinate, Environment}, maze::maze::Cell};
+use crate::{direction::Direction, environment::environment::{Coordinate, Environment}, maze::maze::{Cell, Maze}, topology::Topology};
 
 
 pub async fn render_maze(
@@ -12,7 +12,7 @@ pub async fn render_maze(
     step: usize,
 ) {
     clear_background(BLACK);
-    draw_maze(&environment, cell_size, visited, step, 0.0, 0.0).await;
+    draw_maze(&environment, cell_size, visited, step, 0.0, 0.0, None).await;
     next_frame().await;
 }
 pub async fn draw_maze(
@@ -22,6 +22,7 @@ pub async fn draw_maze(
     step: usize,
     x_offset: f32,
     y_offset: f32,
+    level: Option<usize>,
 ) {
     let current_run = environment.path_followed[step].1;
     draw_text(
@@ -57,54 +58,194 @@ pub async fn draw_maze(
     for i in 0..(step - path_start_index) {
         path_visited.insert(environment.path_followed[i + path_start_index].0);
     }
-    for row in &environment.maze.grid {
-        for cell in row {
-            draw_cell(
-                cell,
-                cell_size,
-                environment,
-                visited,
-                step,
-                &path_visited,
-                x_offset + 30.0,
-                y_offset + 30.0,
-            )
-            .await;
-        }
+    //masked off cells are left as background
+    let level_offset = level_shift(&environment.maze, level, cell_size);
+    for cell in environment
+        .maze
+        .cells()
+        .filter(|cell| environment.maze.is_enabled(cell.coordinate))
+        .filter(|cell| shows_cell(&environment.maze, level, cell.coordinate))
+    {
+        draw_cell(
+            &cell,
+            cell_size,
+            environment,
+            visited,
+            step,
+            &path_visited,
+            x_offset + 30.0 - level_offset,
+            y_offset + 30.0,
+        )
+        .await;
     }
 }
 
+/// Whether a cell is drawn when showing `level`, every level is shown side by side for None
+pub fn shows_cell(maze: &Maze, level: Option<usize>, coordinate: Coordinate) -> bool {
+    level.is_none_or(|level| maze.level_of(coordinate) == level.min(maze.levels() - 1))
+}
+
+/// How far left to move the drawing so the one level being shown starts at the offset
+pub fn level_shift(maze: &Maze, level: Option<usize>, cell_size: f32) -> f32 {
+    level.map_or(0.0, |level| {
+        (level.min(maze.levels() - 1) * (maze.width + 1)) as f32 * cell_size
+    })
+}
+
 
 
-pub fn draw_cell_walls(cell: &Cell, cell_size: f32, x: f32, y: f32, thickness: f32) {
-    if cell.walls.contains(&Direction::North) {
-        draw_line(x, y, x + cell_size, y, thickness, WHITE);
+/// Cells across and down the drawing of a maze takes, hex rows lean right half a cell each and
+/// levels sit side by side a cell apart unless only one is shown
+pub fn maze_extent(maze: &Maze, level: Option<usize>) -> (usize, usize) {
+    match maze.topology {
+        Topology::Square if level.is_none() => (
+            (maze.width + 1) * maze.levels() - 1,
+            maze.level_height(),
+        ),
+        Topology::Square => (maze.width, maze.level_height()),
+        Topology::Hex => (maze.width + maze.height.div_ceil(2), maze.height),
+        Topology::Triangle => (maze.width.div_ceil(2) + 1, maze.height),
     }
-    if cell.walls.contains(&Direction::East) {
-        draw_line(
-            x + cell_size,
-            y,
-            x + cell_size,
-            y + cell_size,
-            thickness,
-            WHITE,
-        );
+}
+
+/// Screen corners of a cell, clockwise so side `i` of `Topology::sides` runs from corner `i`
+/// to corner `i + 1`
+pub fn cell_corners(maze: &Maze, coordinate: Coordinate, cell_size: f32, x_offset: f32, y_offset: f32) -> Vec<Vec2> {
+    let topology = maze.topology;
+    let (x, y) = (coordinate.0 as f32, coordinate.1 as f32);
+    match topology {
+        Topology::Square => {
+            //each level is drawn to the right of the one below it
+            let level = maze.level_of(coordinate);
+            let x = x + (level * (maze.width + 1)) as f32;
+            let y = y - (level * maze.level_height()) as f32;
+            let (left, top) = (x_offset + x * cell_size, y_offset + y * cell_size);
+            vec![
+                Vec2::new(left, top),
+                Vec2::new(left + cell_size, top),
+                Vec2::new(left + cell_size, top + cell_size),
+                Vec2::new(left, top + cell_size),
+            ]
+        }
+        Topology::Hex => {
+            //pointy topped, one cell_size between neighbouring centres
+            let radius = cell_size / 3f32.sqrt();
+            let centre = Vec2::new(
+                x_offset + cell_size * (x + y / 2.0 + 0.5),
+                y_offset + radius * (1.5 * y + 1.0),
+            );
+            (0..6)
+                .map(|corner| {
+                    let angle = ((300 + 60 * corner) % 360) as f32;
+                    let angle = angle.to_radians();
+                    centre + Vec2::new(radius * angle.sin(), -radius * angle.cos())
+                })
+                .collect()
+        }
+        Topology::Triangle => {
+            let row_height = cell_size * 3f32.sqrt() / 2.0;
+            let (left, top) = (x_offset + x * cell_size / 2.0, y_offset + y * row_height);
+            let bottom = top + row_height;
+            if topology.has_side(coordinate, Direction::South) {
+                vec![
+                    Vec2::new(left + cell_size / 2.0, top),
+                    Vec2::new(left + cell_size, bottom),
+                    Vec2::new(left, bottom),
+                ]
+            } else {
+                vec![
+                    Vec2::new(left, top),
+                    Vec2::new(left + cell_size, top),
+                    Vec2::new(left + cell_size / 2.0, bottom),
+                ]
+            }
+        }
+    }
+}
+
+pub fn fill_cell(corners: &[Vec2], colour: Color) {
+    for pair in corners[1..].windows(2) {
+        draw_triangle(corners[0], pair[0], pair[1], colour);
+    }
+}
+
+fn side_corners(topology: Topology, coordinate: Coordinate, corners: &[Vec2], direction: Direction) -> Option<(Vec2, Vec2)> {
+    topology
+        .sides(coordinate)
+        .iter()
+        .position(|side| *side == direction)
+        .map(|index| (corners[index], corners[(index + 1) % corners.len()]))
+}
+
+/// Draws the walls of a cell, passages that wrap round to the far edge are marked in blue,
+/// open stairs with an arrow up or down and tunnels under a crossing with faint lines
+pub fn draw_cell_walls(cell: &Cell, maze: &Maze, corners: &[Vec2], thickness: f32) {
+    draw_stairs(cell, maze, corners);
+    draw_tunnel(cell, corners, thickness);
+    for &direction in maze.sides(cell.coordinate) {
+        let colour = if cell.walls.contains(&direction) {
+            WHITE
+        } else if maze.crosses_seam(cell.coordinate, direction) {
+            SEAM_COLOUR
+        } else {
+            continue;
+        };
+        if let Some((from, to)) = side_corners(maze.topology, cell.coordinate, corners, direction) {
+            draw_line(from.x, from.y, to.x, to.y, thickness, colour);
+        }
     }
-    if cell.walls.contains(&Direction::South) {
-        draw_line(
-            x,
-            y + cell_size,
-            x + cell_size,
-            y + cell_size,
-            thickness,
-            WHITE,
+}
+
+const SEAM_COLOUR: Color = Color::new(0.4, 0.7, 1.0, 0.8);
+
+//stairs up point into the top right of a square cell, stairs down into the bottom left
+fn draw_stairs(cell: &Cell, maze: &Maze, corners: &[Vec2]) {
+    let (top_left, bottom_right) = (corners[0], corners[2]);
+    let size = (bottom_right.x - top_left.x) / 3.0;
+    for direction in maze.open_sides(cell.coordinate).filter(Direction::is_vertical) {
+        let (tip, back) = match direction {
+            Direction::Up => (
+                Vec2::new(bottom_right.x - size, top_left.y + size / 4.0),
+                size,
+            ),
+            _ => (
+                Vec2::new(top_left.x + size, bottom_right.y - size / 4.0),
+                -size,
+            ),
+        };
+        draw_triangle(
+            tip,
+            tip + Vec2::new(-size / 2.0, back),
+            tip + Vec2::new(size / 2.0, back),
+            STAIRS_COLOUR,
         );
     }
-    if cell.walls.contains(&Direction::West) {
-        draw_line(x, y, x, y + cell_size, thickness, WHITE);
+}
+
+//the tunnel runs between the walls of the passage crossing over it, a quarter in from each side
+fn draw_tunnel(cell: &Cell, corners: &[Vec2], thickness: f32) {
+    let (top_left, bottom_right) = (corners[0], corners[2]);
+    let size = bottom_right - top_left;
+    for inset in [0.25, 0.75] {
+        let (from, to) = match cell.under {
+            Some(Direction::East) => (
+                Vec2::new(top_left.x, top_left.y + size.y * inset),
+                Vec2::new(bottom_right.x, top_left.y + size.y * inset),
+            ),
+            Some(_) => (
+                Vec2::new(top_left.x + size.x * inset, top_left.y),
+                Vec2::new(top_left.x + size.x * inset, bottom_right.y),
+            ),
+            None => return,
+        };
+        draw_line(from.x, from.y, to.x, to.y, thickness, TUNNEL_COLOUR);
     }
 }
 
+const TUNNEL_COLOUR: Color = Color::new(0.6, 0.6, 0.6, 0.6);
+
+const STAIRS_COLOUR: Color = Color::new(0.9, 0.6, 1.0, 0.9);
+
 pub async fn draw_cell(
     cell: &Cell,
     cell_size: f32,
@@ -115,35 +256,69 @@ pub async fn draw_cell(
     x_offset: f32,
     y_offset: f32,
 ) {
-    let x = cell.coordinate.0 as f32 * cell_size + x_offset;
-    let y = cell.coordinate.1 as f32 * cell_size + y_offset;
+    let topology = environment.maze.topology;
     let coordinates = (cell.coordinate.0, cell.coordinate.1);
+    let corners = cell_corners(&environment.maze, coordinates, cell_size, x_offset, y_offset);
+    let centre = corners.iter().copied().sum::<Vec2>() / corners.len() as f32;
+    //triangles are a good deal thinner than their side length
+    let marker_size = match topology {
+        Topology::Triangle => cell_size / 5.0,
+        _ => cell_size / 3.0,
+    };
 
-    if cell.walls.len() == 4 {
-        draw_rectangle(x, y, cell_size, cell_size, WHITE);
+    if environment.maze.open_sides(coordinates).next().is_none() {
+        fill_cell(&corners, WHITE);
     } else {
-        draw_rectangle(x, y, cell_size, cell_size, BLACK);
+        fill_cell(&corners, BLACK);
     }
 
     if visited.contains(&coordinates) {
-        draw_rectangle(x, y, cell_size, cell_size, LIGHTGRAY);
+        fill_cell(&corners, LIGHTGRAY);
+    }
+
+    //dearer terrain is tinted a deeper brown
+    let cost = environment.maze.cost(coordinates);
+    if cost > 1 {
+        let depth = cost as f32 / environment.maze.max_cost() as f32;
+        fill_cell(&corners, Color::new(0.55, 0.35, 0.15, 0.6 * depth));
     }
 
     if environment.maze.end.contains(&coordinates) {
-        draw_rectangle(x, y, cell_size, cell_size, GOLD); // Change RED to any color you prefer
+        fill_cell(&corners, GOLD); // Change RED to any color you prefer
     }
 
     if coordinates == environment.maze.start {
-        draw_rectangle(x, y, cell_size, cell_size, GREEN);
+        fill_cell(&corners, GREEN);
     }
 
     if path.contains(&coordinates) {
-        draw_rectangle(x, y, cell_size, cell_size, PINK);
+        fill_cell(&corners, PINK);
     }
 
     if environment.path_followed[step].0 == coordinates {
-        draw_rectangle(x, y, cell_size, cell_size, RED);
+        fill_cell(&corners, RED);
+    }
+    if environment.maze.key_at(coordinates).is_some() {
+        let half = marker_size / 2.0;
+        draw_rectangle(centre.x - half, centre.y - half, marker_size, marker_size, YELLOW);
+    }
+    //both ends of a portal share a colour so pairs can be matched up
+    if let Some(pair) = environment
+        .maze
+        .portals
+        .iter()
+        .position(|portal| portal.a == coordinates || portal.b == coordinates)
+    {
+        let colour = PORTAL_COLOURS[pair % PORTAL_COLOURS.len()];
+        draw_circle(centre.x, centre.y, marker_size, colour);
     }
     // Draw the cell walls based on its directions
-    draw_cell_walls(cell, cell_size, x, y, 1.0);
-}
\ No newline at end of file
+    draw_cell_walls(cell, &environment.maze, &corners, 1.0);
+    for door in environment.maze.doors.iter().filter(|door| door.coordinate == coordinates) {
+        if let Some((from, to)) = side_corners(topology, coordinates, &corners, door.direction) {
+            draw_line(from.x, from.y, to.x, to.y, 3.0, ORANGE);
+        }
+    }
+}
+
+const PORTAL_COLOURS: [Color; 4] = [BLUE, PURPLE, SKYBLUE, VIOLET];
@@ -0,0 +1,36 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Serialisable source of rngs for an environment. Each draw is seeded from the base seed and
+/// the draw count, so a saved environment hands out the same rngs once it is reloaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RngStream {
+    pub seed: u64,
+    pub draws: u64,
+}
+
+impl RngStream {
+    pub fn new(rng_seed: Option<u64>) -> RngStream {
+        RngStream {
+            seed: rng_seed.unwrap_or_else(|| rand::rng().random()),
+            draws: 0,
+        }
+    }
+
+    pub fn next_rng(&mut self) -> StdRng {
+        //splitmix64 so neighbouring draws get unrelated seeds
+        let mut z = self
+            .seed
+            .wrapping_add((self.draws + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        self.draws += 1;
+        StdRng::seed_from_u64(z ^ (z >> 31))
+    }
+}
+
+impl Default for RngStream {
+    fn default() -> Self {
+        RngStream::new(None)
+    }
+}
@@ -1,47 +1,40 @@
-use std::{
-    collections::{HashMap, HashSet},
-    vec,
-};
+use std::{collections::HashSet, vec};
 
-use crate::environment::environment::{Coordinate, Environment};
+use crate::{
+    environment::environment::{Coordinate, Environment},
+    solving_algorithms::solve_handler::{next_states, SearchState},
+};
 
 
-pub fn solve_maze_dfs(env: &Environment, end: Coordinate) -> Vec<Coordinate> {
-    let mut stack: Vec<((usize, usize), usize)> = vec![(env.maze.get_starting_point(), 0)]; // Stack for DFS
-    let mut visited = HashSet::new(); // Track visited cells
-    let mut path = vec![]; // Final path to the goal
+/// Depth first path from `start` to the first of `targets` it runs into, going back for keys
+/// when a door is in the way
+pub fn solve_maze_dfs(
+    env: &Environment,
+    start: &SearchState,
+    targets: &HashSet<Coordinate>,
+) -> Vec<SearchState> {
+    let mut stack: Vec<(SearchState, usize)> = vec![(start.clone(), 0)]; // Stack for DFS
+    let mut visited = HashSet::new(); // Track visited cells and the keys held there
+    let mut path: Vec<(SearchState, usize)> = vec![]; // Final path to the goal
     let mut step = 0;
-    let weighted_graph = &env.weighted_graph;
     while let Some(current) = stack.pop() {
         if visited.contains(&current.0) {
             continue;
         }
 
         if step > current.1 {
-            // env.maze.take_step((step-current.1)*2);
-            path = path
-                .into_iter()
-                .filter(|x: &(_, usize)| x.1 < current.1)
-                .collect();
+            path.retain(|x| x.1 < current.1);
         }
         step = current.1 + 1;
-        path.push(current);
-        visited.insert(current.0);
+        path.push(current.clone());
+        visited.insert(current.0.clone());
 
         // If we've reached the end, return the path
-        if current.0 == end {
-            // env.maze.take_step(step-1);
-            return path.into_iter().map(|(coords, _)| coords).collect();
+        if targets.contains(&current.0 .0) {
+            return path.into_iter().map(|(state, _)| state).collect();
         }
         // Explore neighbors
-        for (direction, steps) in weighted_graph.get(&current.0).unwrap_or(&HashMap::new()) {
-            let neighbor = match env.maze.move_from(direction, &current.0, *steps) {
-                Ok(coordinates) => coordinates,
-                Err(_) => {
-                    continue;
-                }
-            };
-
+        for (neighbor, _) in next_states(env, &current.0) {
             if !visited.contains(&neighbor) {
                 stack.push((neighbor, step));
             }
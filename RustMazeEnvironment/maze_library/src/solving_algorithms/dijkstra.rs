@@ -1,20 +1,52 @@
 use std::{
     cmp::Reverse,
-    collections::{BinaryHeap, HashMap, HashSet},
+    collections::{BTreeSet, BinaryHeap, HashMap, HashSet},
+    fmt::Debug,
+    hash::Hash,
+};
+use crate::{
+    environment::environment::{Coordinate, Environment},
+    solving_algorithms::solve_handler::{next_states, search_state, SearchState},
 };
-use crate::
-    environment::environment::{Coordinate, Environment}
-;
 
 pub fn dijkstra_solve(env: &Environment, start: Coordinate, end: Coordinate) -> Vec<Coordinate> {
-    let path_map = dijkstra_graph(env, start);
+    let start = search_state(env, start, &BTreeSet::new());
+    dijkstra_solve_to_nearest(env, &start, &HashSet::from([end]))
+        .into_iter()
+        .map(|(coordinate, _)| coordinate)
+        .collect()
+}
+
+/// Shortest path to whichever target is closest, empty when none can be reached
+pub fn dijkstra_solve_to_nearest(
+    env: &Environment,
+    start: &SearchState,
+    targets: &HashSet<Coordinate>,
+) -> Vec<SearchState> {
+    let path_map = dijkstra_states(env, start);
+    //ties go to the smallest coordinate so the route does not depend on set order
+    match path_map
+        .iter()
+        .filter(|(state, _)| targets.contains(&state.0))
+        .map(|(state, (distance, _))| (*distance, state))
+        .min()
+    {
+        Some((_, nearest)) => reconstruct_path(&path_map, start, nearest),
+        None => vec![],
+    }
+}
 
+fn reconstruct_path<T: Clone + Eq + Hash + Debug>(
+    path_map: &HashMap<T, (usize, T)>,
+    start: &T,
+    end: &T,
+) -> Vec<T> {
     let mut previous = end;
     let mut path_followed = vec![];
     while previous != start {
-        if let Some((_, current)) = path_map.get(&previous) {
-            path_followed.push(previous);
-            previous = *current;
+        if let Some((_, current)) = path_map.get(previous) {
+            path_followed.push(previous.clone());
+            previous = current;
         } else {
             panic!(
                 "Failed to reconstruct the path: Node {:?} is unreachable.",
@@ -22,51 +54,72 @@ pub fn dijkstra_solve(env: &Environment, start: Coordinate, end: Coordinate) ->
             );
         }
     }
-    path_followed.push(start);
+    path_followed.push(start.clone());
     path_followed.reverse();
     path_followed
 }
 
+/// Distance to every cell reachable from the nearest of `starts` and the cell it is reached
+/// from, fetching keys for any doors on the way
 pub fn dijkstra_graph(
     env: &Environment,
-    start: Coordinate,
+    starts: &[Coordinate],
 ) -> HashMap<Coordinate, (usize, Coordinate)> {
-    let mut main_stack = BinaryHeap::new(); 
     let mut path_map: HashMap<Coordinate, (usize, Coordinate)> = HashMap::new();
-    let mut visited = HashSet::new();  
-    let weighted_graph = &env.weighted_graph;
-    path_map.insert(start, (0, start));
-    main_stack.push(Reverse((0, start)));
+    let starts: Vec<SearchState> = starts
+        .iter()
+        .map(|start| search_state(env, *start, &BTreeSet::new()))
+        .collect();
+    for ((cell, _), (distance, (previous, _))) in dijkstra_from_states(env, &starts) {
+        if path_map.get(&cell).is_none_or(|(best, _)| distance < *best) {
+            path_map.insert(cell, (distance, previous));
+        }
+    }
+    path_map
+}
+
+/// Dijkstra over cells and the keys held in them, the same cell is reached again whenever
+/// it is reached holding different keys
+pub fn dijkstra_states(
+    env: &Environment,
+    start: &SearchState,
+) -> HashMap<SearchState, (usize, SearchState)> {
+    dijkstra_from_states(env, std::slice::from_ref(start))
+}
+
+//every start is reached from itself at no cost
+fn dijkstra_from_states(
+    env: &Environment,
+    starts: &[SearchState],
+) -> HashMap<SearchState, (usize, SearchState)> {
+    let mut main_stack = BinaryHeap::new();
+    let mut path_map: HashMap<SearchState, (usize, SearchState)> = HashMap::new();
+    let mut visited = HashSet::new();
+    for start in starts {
+        path_map.insert(start.clone(), (0, start.clone()));
+        main_stack.push(Reverse((0, start.clone())));
+    }
 
     while let Some(Reverse((distance, current))) = main_stack.pop() {
         if visited.contains(&current) {
             continue;
         }
 
-        visited.insert(current);
+        visited.insert(current.clone());
 
-        let neighbors: Vec<(Coordinate, usize)> = weighted_graph
-            .get(&current)
-            .unwrap_or(&HashMap::new())
-            .iter()
-            .filter_map(|(direction, &steps)| {
-                match env.maze.move_from(direction, &current, steps) {
-                    Ok(new_coordinates) => {
-                        if visited.contains(&new_coordinates) {
-                            return None;
-                        }
-                        Some((new_coordinates, steps))
-                    }
-                    Err(_) => None,
-                }
-            })
+        let neighbors: Vec<(SearchState, usize)> = next_states(env, &current)
+            .into_iter()
+            .filter(|(state, _)| !visited.contains(state))
             .collect();
 
         for (neighbor, weight) in neighbors {
             let tentative_distance = distance + weight;
 
-            if !path_map.contains_key(&neighbor) || tentative_distance < path_map[&neighbor].0 {
-                path_map.insert(neighbor, (tentative_distance, current));
+            if path_map
+                .get(&neighbor)
+                .is_none_or(|(best, _)| tentative_distance < *best)
+            {
+                path_map.insert(neighbor.clone(), (tentative_distance, current.clone()));
                 main_stack.push(Reverse((tentative_distance, neighbor)));
             }
         }
@@ -1,10 +1,10 @@
-use std::str::FromStr;
+use std::{collections::BTreeSet, str::FromStr};
 
 use clap::ValueEnum;
 
-use crate::{direction::{direction_between, Direction}, environment::environment::{Coordinate, Environment}};
+use crate::{direction::Direction, environment::environment::{graph_edge_direction, Coordinate, Environment}, goals::GoalProgress};
 
-use super::{dfs_search::solve_maze_dfs, dijkstra::dijkstra_solve};
+use super::{dfs_search::solve_maze_dfs, dijkstra::dijkstra_solve_to_nearest};
 
 #[derive(ValueEnum, Clone, Debug)]
 pub enum SolveAlgorithm {
@@ -24,21 +24,83 @@ impl FromStr for SolveAlgorithm {
     }
 }
 
-pub fn select_maze_solve_algorithm(environment: &Environment, algorithm: &SolveAlgorithm) -> Vec<(Coordinate, Direction)>{
-    let maze = &environment.maze;
-    let path = match algorithm {
-        SolveAlgorithm::Dfs => {
-            solve_maze_dfs(environment, *environment.maze.end.iter().next().unwrap())
+/// Where a search has got to, the cell and the keys picked up on the way there
+pub type SearchState = (Coordinate, BTreeSet<usize>);
+
+/// The search state of standing on `cell` holding `keys`, picking up any key lying there
+pub fn search_state(environment: &Environment, cell: Coordinate, keys: &BTreeSet<usize>) -> SearchState {
+    let mut keys = keys.clone();
+    if let Some(key) = environment.maze.key_at(cell) {
+        keys.insert(key);
+    }
+    (cell, keys)
+}
+
+/// Weighted graph edges out of a search state with what they cost, doors whose key is not
+/// held are walls
+pub fn next_states(environment: &Environment, (cell, keys): &SearchState) -> Vec<(SearchState, usize)> {
+    environment
+        .weighted_graph
+        .get(cell)
+        .into_iter()
+        .flatten()
+        .filter(|(direction, _)| {
+            environment
+                .maze
+                .door_between(*cell, **direction)
+                .is_none_or(|door| keys.contains(&door.key))
+        })
+        .filter_map(|(direction, &steps)| {
+            let landing = environment.maze.destination(direction, cell, steps).ok()?;
+            Some((
+                search_state(environment, landing, keys),
+                environment.maze.edge_cost(direction, cell, steps),
+            ))
+        })
+        .collect()
+}
+
+/// Route from the start that completes the goal mode, one leg per goal reached. Dijkstra
+/// legs go to the nearest goal that still counts, so visiting all goals is greedy rather
+/// than the shortest tour. Keys are fetched on the way when a door is in the way.
+pub fn solve_goal_route(environment: &Environment, algorithm: &SolveAlgorithm) -> Vec<Coordinate> {
+    let mut route = vec![environment.maze.start];
+    let mut state = search_state(environment, environment.maze.start, &BTreeSet::new());
+    let mut progress = GoalProgress::from_path(
+        environment.config.goal_mode,
+        environment.maze.goal_regions(),
+        &route,
+    );
+    while !progress.is_complete() {
+        let targets = progress.target_cells();
+        let leg = match algorithm {
+            SolveAlgorithm::Dfs => solve_maze_dfs(environment, &state, &targets),
+            SolveAlgorithm::Dijkstra => dijkstra_solve_to_nearest(environment, &state, &targets),
+        };
+        //no goal left in reach
+        if leg.len() < 2 {
+            break;
+        }
+        for (coordinate, _) in leg.iter().skip(1) {
+            progress.visit(*coordinate);
+            route.push(*coordinate);
         }
-        SolveAlgorithm::Dijkstra => dijkstra_solve(
-            &environment,
-            maze.start,
-            *environment.maze.end.iter().next().unwrap(),
-        ),
-    };
+        state = leg[leg.len() - 1].clone();
+    }
+    route
+}
+
+pub fn select_maze_solve_algorithm(environment: &Environment, algorithm: &SolveAlgorithm) -> Vec<(Coordinate, Direction)>{
+    let path = solve_goal_route(environment, algorithm);
     let mut vec_with_direction = vec![];
     for index in 1..path.len() {
-        let direction = direction_between(path[index - 1], path[index]).unwrap();
+        let direction = graph_edge_direction(
+            &environment.maze,
+            &environment.weighted_graph,
+            path[index - 1],
+            path[index],
+        )
+        .unwrap();
         vec_with_direction.push((path[index - 1], direction));
     }
     vec_with_direction
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use crate::{direction::Direction, maze::maze::Maze};
+use crate::maze::maze::Maze;
 
 pub fn all_tiles_reachable(maze: &Maze) -> bool {
     let mut stack = vec![*maze.end.iter().next().unwrap()]; // Stack for DFS
@@ -11,25 +11,18 @@ pub fn all_tiles_reachable(maze: &Maze) -> bool {
         }
         visited.insert(current);
 
-        for direction in &[
-            Direction::North,
-            Direction::South,
-            Direction::East,
-            Direction::West,
-        ] {
-            let neighbor = match maze.move_from(direction, &current, 1) {
+        for direction in maze.sides(current) {
+            let neighbor = match maze.move_from_with_walls(direction, &current, 1) {
                 Ok(coordinates) => coordinates,
                 Err(_) => {
                     continue;
                 }
             };
 
-            if !visited.contains(&neighbor)
-                && !maze.grid[current.0][current.1].walls.contains(direction)
-            {
+            if !visited.contains(&neighbor) {
                 stack.push(neighbor);
             }
         }
     }
-    visited.len() == maze.width * maze.height
+    visited.len() == maze.enabled_count()
 }
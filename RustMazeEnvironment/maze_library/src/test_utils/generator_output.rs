@@ -0,0 +1,48 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    direction::Direction,
+    environment::environment::Coordinate,
+    maze::maze::Maze,
+    maze_gen::maze_gen_handler::{generate_maze_type, GenerationParameters, MazeType},
+    test_utils::all_tiles_reachable::all_tiles_reachable,
+};
+
+/// Passages exactly as the generator hands them back, before any fitting to the maze
+pub fn raw_passages(maze: &Maze, maze_type: &MazeType, seed: u64) -> Vec<(Coordinate, Direction)> {
+    generate_maze_type(
+        maze,
+        StdRng::seed_from_u64(seed),
+        maze_type,
+        &GenerationParameters::default(),
+    )
+}
+
+/// The first passage that is not a side between two enabled cells
+pub fn first_invalid_passage(
+    maze: &Maze,
+    walls: &[(Coordinate, Direction)],
+) -> Option<(Coordinate, Direction)> {
+    walls
+        .iter()
+        .find(|(coordinate, direction)| {
+            !maze.is_enabled(*coordinate)
+                || !maze.sides(*coordinate).contains(direction)
+                || maze.move_from(direction, coordinate, 1).is_err()
+        })
+        .copied()
+}
+
+/// Checks every passage is a side between two enabled cells and that the passages alone
+/// reach every enabled cell
+pub fn check_raw_passages(maze: &Maze, walls: &[(Coordinate, Direction)]) -> Result<(), String> {
+    if let Some((coordinate, direction)) = first_invalid_passage(maze, walls) {
+        return Err(format!("{:?} {} is not a side", coordinate, direction));
+    }
+    let mut carved = maze.clone();
+    carved.break_walls_for_path(walls.to_vec());
+    if !all_tiles_reachable(&carved) {
+        return Err("some cells are cut off".to_string());
+    }
+    Ok(())
+}
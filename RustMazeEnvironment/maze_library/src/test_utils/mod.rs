@@ -1 +1,3 @@
 pub mod all_tiles_reachable;
+pub mod uniformity;
+pub mod generator_output;
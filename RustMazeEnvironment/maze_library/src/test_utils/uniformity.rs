@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, SeedableRng};
+use union_find::{QuickUnionUf, UnionBySize, UnionFind};
+
+use crate::{direction::Direction, environment::environment::Coordinate, maze::maze::Maze};
+
+/// z score for p = 0.001, used with the Wilson-Hilferty approximation of the chi-square quantile
+const CRITICAL_Z: f64 = 3.090;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniformityResult {
+    pub chi_square: f64,
+    pub degrees_of_freedom: usize,
+    pub critical_value: f64,
+    /// samples whose wall list was not a spanning tree of the grid
+    pub invalid_samples: usize,
+}
+
+impl UniformityResult {
+    pub fn is_uniform(&self) -> bool {
+        self.invalid_samples == 0 && self.chi_square < self.critical_value
+    }
+}
+
+/// Every interior edge of the grid, stored from the west or north cell
+fn grid_edges(width: usize, height: usize) -> Vec<(Coordinate, Direction)> {
+    let mut edges = vec![];
+    for x in 0..width {
+        for y in 0..height {
+            if x + 1 < width {
+                edges.push(((x, y), Direction::East));
+            }
+            if y + 1 < height {
+                edges.push(((x, y), Direction::South));
+            }
+        }
+    }
+    edges
+}
+
+fn is_spanning_tree(width: usize, height: usize, edges: &[(Coordinate, Direction)]) -> bool {
+    if edges.len() + 1 != width * height {
+        return false;
+    }
+    let mut union_find = QuickUnionUf::<UnionBySize>::new(width * height);
+    for ((x, y), direction) in edges.iter() {
+        let (nx, ny) = match direction {
+            Direction::East => (x + 1, *y),
+            _ => (*x, y + 1),
+        };
+        if !union_find.union(y * width + x, ny * width + nx) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Bitmask over `grid_edges` identifying the tree, None if the walls are not a spanning tree
+pub fn tree_key(maze: &Maze, walls: &[(Coordinate, Direction)]) -> Option<u64> {
+    let edges = grid_edges(maze.width, maze.height);
+    let mut normalised = vec![];
+    let mut key = 0u64;
+    for (coordinate, direction) in walls.iter() {
+        let neighbour = maze.move_from(direction, coordinate, 1).ok()?;
+        let edge = match direction {
+            Direction::East | Direction::South => (*coordinate, *direction),
+            _ => (neighbour, direction.opposite_direction()),
+        };
+        let index = edges.iter().position(|e| *e == edge)?;
+        if key & (1 << index) != 0 {
+            return None;
+        }
+        key |= 1 << index;
+        normalised.push(edge);
+    }
+    if !is_spanning_tree(maze.width, maze.height, &normalised) {
+        return None;
+    }
+    Some(key)
+}
+
+/// Brute force list of every spanning tree of a small grid as `tree_key` bitmasks
+pub fn spanning_trees(width: usize, height: usize) -> Vec<u64> {
+    let edges = grid_edges(width, height);
+    assert!(edges.len() < 25, "grid too large to enumerate");
+    (0u64..(1 << edges.len()))
+        .filter(|mask| mask.count_ones() as usize + 1 == width * height)
+        .filter(|mask| {
+            let chosen: Vec<(Coordinate, Direction)> = edges
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| mask & (1 << index) != 0)
+                .map(|(_, edge)| *edge)
+                .collect();
+            is_spanning_tree(width, height, &chosen)
+        })
+        .collect()
+}
+
+/// Runs `generate` with seeds 0..samples and tests the tree frequencies against a uniform spread
+pub fn chi_square_uniformity(
+    width: usize,
+    height: usize,
+    samples: u64,
+    generate: impl Fn(&Maze, StdRng) -> Vec<(Coordinate, Direction)>,
+) -> UniformityResult {
+    let trees = spanning_trees(width, height);
+    let mut maze = Maze::new(width, height);
+    maze.set_end((width / 2, height / 2));
+
+    let mut observed: HashMap<u64, usize> = trees.iter().map(|tree| (*tree, 0)).collect();
+    let mut invalid_samples = 0;
+    for seed in 0..samples {
+        let walls = generate(&maze, StdRng::seed_from_u64(seed));
+        match tree_key(&maze, &walls).and_then(|key| observed.get_mut(&key)) {
+            Some(count) => *count += 1,
+            None => invalid_samples += 1,
+        }
+    }
+
+    let expected = samples as f64 / trees.len() as f64;
+    let chi_square = observed
+        .values()
+        .map(|count| (*count as f64 - expected).powi(2) / expected)
+        .sum();
+    let degrees_of_freedom = trees.len() - 1;
+    let df = degrees_of_freedom as f64;
+    let critical_value =
+        df * (1.0 - 2.0 / (9.0 * df) + CRITICAL_Z * (2.0 / (9.0 * df)).sqrt()).powi(3);
+    UniformityResult {
+        chi_square,
+        degrees_of_freedom,
+        critical_value,
+        invalid_samples,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze_gen::{
+        aldous_broder::random_aldous_broder_maze, binary_tree::random_binary_maze,
+        wilsons::random_wilson_maze,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_spanning_tree_counts() {
+        assert_eq!(spanning_trees(2, 2).len(), 4);
+        assert_eq!(spanning_trees(3, 3).len(), 192);
+    }
+
+    #[test]
+    fn test_wilsons_is_uniform() {
+        let result = chi_square_uniformity(3, 3, 192 * 50, random_wilson_maze);
+        assert!(result.is_uniform(), "{:?}", result);
+    }
+
+    #[test]
+    fn test_aldous_broder_is_uniform() {
+        let result = chi_square_uniformity(3, 3, 192 * 50, random_aldous_broder_maze);
+        assert!(result.is_uniform(), "{:?}", result);
+    }
+
+    #[test]
+    fn test_binary_tree_is_biased() {
+        let result = chi_square_uniformity(3, 3, 192 * 50, random_binary_maze);
+        assert!(!result.is_uniform(), "{:?}", result);
+    }
+}
@@ -0,0 +1,208 @@
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use crate::{direction::Direction, environment::environment::Coordinate};
+
+const SQUARE_SIDES: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+const HEX_SIDES: [Direction; 6] = [
+    Direction::North,
+    Direction::NorthEast,
+    Direction::East,
+    Direction::South,
+    Direction::SouthWest,
+    Direction::West,
+];
+const HEX_DIRECTIONS: [Direction; 6] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+    Direction::NorthEast,
+    Direction::SouthWest,
+];
+const UP_TRIANGLE_SIDES: [Direction; 3] = [Direction::East, Direction::South, Direction::West];
+const DOWN_TRIANGLE_SIDES: [Direction; 3] = [Direction::North, Direction::East, Direction::West];
+
+/// How the cells of a maze fit together
+#[derive(
+    ValueEnum,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Hash,
+    Eq,
+    PartialEq,
+    EnumIter,
+    Serialize,
+    Deserialize,
+    Display,
+)]
+pub enum Topology {
+    #[default]
+    Square,
+    /// hexagons in axial coordinates, the grid is drawn as a rhombus. North and South lean
+    /// left and right of vertical, NorthEast and SouthWest fill in the other two sides
+    Hex,
+    /// triangles alternating point up and point down, the cell at (0, 0) points up. Up
+    /// triangles have a South side and down triangles a North side
+    Triangle,
+}
+
+impl FromStr for Topology {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "square" => Ok(Topology::Square),
+            "hex" | "hexagonal" => Ok(Topology::Hex),
+            "triangle" | "triangular" => Ok(Topology::Triangle),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Topology {
+    /// The sides of the cell at `coordinate`, clockwise
+    pub fn sides(&self, coordinate: Coordinate) -> &'static [Direction] {
+        match self {
+            Topology::Square => &SQUARE_SIDES,
+            Topology::Hex => &HEX_SIDES,
+            Topology::Triangle if Topology::points_up(coordinate) => &UP_TRIANGLE_SIDES,
+            Topology::Triangle => &DOWN_TRIANGLE_SIDES,
+        }
+    }
+
+    pub fn has_side(&self, coordinate: Coordinate, direction: Direction) -> bool {
+        match self {
+            Topology::Square => !direction.is_diagonal() && !direction.is_vertical(),
+            _ => self.sides(coordinate).contains(&direction),
+        }
+    }
+
+    /// Every direction any cell can have a side in, in the order observations list them
+    pub fn directions(&self) -> &'static [Direction] {
+        match self {
+            Topology::Square | Topology::Triangle => &SQUARE_SIDES,
+            Topology::Hex => &HEX_DIRECTIONS,
+        }
+    }
+
+    fn points_up(coordinate: Coordinate) -> bool {
+        (coordinate.0 + coordinate.1).is_multiple_of(2)
+    }
+
+    /// Where a side faces on screen, in degrees clockwise from straight up
+    pub fn angle(&self, direction: Direction) -> u32 {
+        match (self, direction) {
+            (Topology::Hex, Direction::North) => 330,
+            (Topology::Hex, Direction::South) => 150,
+            (_, Direction::NorthEast) => 30,
+            (_, Direction::SouthWest) => 210,
+            //the stairs sit between the corridors so wall following keeps one order round a cell
+            (_, Direction::Up) => 45,
+            (_, Direction::Down) => 225,
+            (_, Direction::North) => 0,
+            (_, Direction::East) => 90,
+            (_, Direction::South) => 180,
+            (_, Direction::West) => 270,
+        }
+    }
+
+    /// How many actions an agent picks between
+    pub fn action_count(&self) -> usize {
+        match self {
+            Topology::Square => 4,
+            Topology::Hex => 6,
+            Topology::Triangle => 3,
+        }
+    }
+
+    /// The side an action picks at `coordinate`, triangles read 0 as whichever of North or
+    /// South the cell has, 1 as East and 2 as West
+    pub fn action_direction(&self, coordinate: Coordinate, action: usize) -> Direction {
+        match self {
+            Topology::Square | Topology::Hex => Direction::from(action % self.action_count()),
+            Topology::Triangle => match action % 3 {
+                0 if Topology::points_up(coordinate) => Direction::South,
+                0 => Direction::North,
+                1 => Direction::East,
+                _ => Direction::West,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::{
+        maze::maze::Maze,
+        maze_gen::maze_gen_handler::MazeType,
+        test_utils::generator_output::{check_raw_passages, raw_passages},
+    };
+
+    #[test]
+    fn test_sides_pair_up_with_their_neighbours() {
+        for topology in [Topology::Square, Topology::Hex, Topology::Triangle] {
+            for coordinate in [(2, 2), (3, 2), (2, 3)] {
+                for direction in topology.sides(coordinate) {
+                    let (dx, dy) = direction.offset();
+                    let neighbour = (
+                        (coordinate.0 as i32 + dx) as usize,
+                        (coordinate.1 as i32 + dy) as usize,
+                    );
+                    assert!(
+                        topology.has_side(neighbour, direction.opposite_direction()),
+                        "{} {:?} {}",
+                        topology,
+                        coordinate,
+                        direction
+                    );
+                }
+            }
+        }
+        assert_eq!(Topology::Triangle.action_direction((1, 0), 0), Direction::North);
+        assert_eq!(
+            Maze::new_with_topology(4, 4, Topology::Hex).input_features(),
+            crate::constants::constants::NUMBER_OF_INPUT_FEATURES + 56
+        );
+    }
+
+    #[test]
+    fn test_every_generator_carves_hex_and_triangle_sides_itself() {
+        for topology in [Topology::Hex, Topology::Triangle] {
+            for maze_type in MazeType::iter() {
+                let maze = Maze::init_maze_with_topology(12, 12, topology);
+                let walls = raw_passages(&maze, &maze_type, 4);
+
+                assert_eq!(
+                    check_raw_passages(&maze, &walls),
+                    Ok(()),
+                    "{} on {}",
+                    maze_type,
+                    topology
+                );
+                if topology == Topology::Hex {
+                    assert!(
+                        walls.iter().any(|(_, direction)| {
+                            matches!(direction, Direction::NorthEast | Direction::SouthWest)
+                        }),
+                        "{} carves no diagonals",
+                        maze_type
+                    );
+                }
+            }
+        }
+    }
+}